@@ -0,0 +1,868 @@
+use std::{collections::HashMap, sync::{atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering}, Arc, Mutex}};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+use crate::ae2d::Window::Window;
+
+pub struct Clip
+{
+	samples: Vec<f32>,
+	channels: u16,
+	sampleRate: u32
+}
+
+impl Clip
+{
+	pub fn load(path: String) -> Option<Self>
+	{
+		let ext = std::path::Path::new(&path).extension()
+			.unwrap_or_default().to_string_lossy().to_lowercase();
+		match ext.as_str()
+		{
+			"wav" => Self::loadWAV(&path),
+			"ogg" => Self::loadOGG(&path),
+			x =>
+			{
+				println!("Failed to load sound from {path}: unknown format '{x}'");
+				None
+			}
+		}
+	}
+
+	fn loadWAV(path: &str) -> Option<Self>
+	{
+		let reader = hound::WavReader::open(path);
+		if let Err(x) = reader
+		{
+			println!("Error on reading sound from {path}:\n{x}");
+			return None;
+		}
+		let reader = reader.unwrap();
+		let spec = reader.spec();
+
+		let samples = match spec.sample_format
+		{
+			hound::SampleFormat::Float => reader.into_samples::<f32>()
+				.filter_map(|x| x.ok())
+				.collect(),
+			hound::SampleFormat::Int =>
+			{
+				let max = (1i64 << (spec.bits_per_sample - 1)) as f32;
+				reader.into_samples::<i32>()
+					.filter_map(|x| x.ok())
+					.map(|x| x as f32 / max)
+					.collect()
+			}
+		};
+
+		Some(Self
+		{
+			samples,
+			channels: spec.channels,
+			sampleRate: spec.sample_rate
+		})
+	}
+
+	fn loadOGG(path: &str) -> Option<Self>
+	{
+		let file = std::fs::File::open(path);
+		if let Err(x) = file
+		{
+			println!("Error on reading sound from {path}:\n{x}");
+			return None;
+		}
+		let reader = lewton::inside_ogg::OggStreamReader::new(file.unwrap());
+		if let Err(x) = reader
+		{
+			println!("Error on decoding sound from {path}:\n{x}");
+			return None;
+		}
+		let mut reader = reader.unwrap();
+
+		let mut samples = vec![];
+		while let Ok(Some(packet)) = reader.read_dec_packet_itl()
+		{
+			samples.extend(packet.iter().map(|x| *x as f32 / 32768.0));
+		}
+
+		Some(Self
+		{
+			samples,
+			channels: reader.ident_hdr.audio_channels as u16,
+			sampleRate: reader.ident_hdr.audio_sample_rate
+		})
+	}
+
+	pub fn getChannels(&self) -> u16 { self.channels }
+
+	pub fn getSampleRate(&self) -> u32 { self.sampleRate }
+
+	pub fn getDuration(&self) -> f32
+	{
+		if self.channels == 0 || self.sampleRate == 0 { return 0.0; }
+		(self.samples.len() / self.channels as usize) as f32 / self.sampleRate as f32
+	}
+
+	fn frameCount(&self) -> usize
+	{
+		if self.channels == 0 { return 0; }
+		self.samples.len() / self.channels as usize
+	}
+
+	fn frame(&self, id: usize) -> (f32, f32)
+	{
+		let c = self.channels as usize;
+		let i = id * c;
+		if c == 1 { (self.samples[i], self.samples[i]) }
+		else { (self.samples[i], self.samples[i + 1]) }
+	}
+}
+
+pub struct Voice
+{
+	clip: Arc<Clip>,
+	cursor: f64,
+	pub volume: f32,
+	pub pitch: f32,
+	pub looping: bool,
+	pub paused: bool,
+	pub pan: f32,
+	pub position: Option<glam::Vec2>,
+	pub radius: f32,
+	attenuation: f32
+}
+
+impl Voice
+{
+	pub fn new(clip: Arc<Clip>) -> Self
+	{
+		Self
+		{
+			clip,
+			cursor: 0.0,
+			volume: 1.0,
+			pitch: 1.0,
+			looping: false,
+			paused: false,
+			pan: 0.0,
+			position: None,
+			radius: 0.0,
+			attenuation: 1.0
+		}
+	}
+
+	fn sample(&mut self, sampleRate: u32) -> Option<(f32, f32)>
+	{
+		let count = self.clip.frameCount();
+		if count == 0 { return None; }
+
+		if self.cursor as usize >= count
+		{
+			if !self.looping { return None; }
+			self.cursor %= count as f64;
+		}
+
+		let id = self.cursor as usize;
+		let next = if id + 1 < count { id + 1 }
+			else if self.looping { 0 } else { id };
+		let t = (self.cursor - id as f64) as f32;
+		let a = self.clip.frame(id);
+		let b = self.clip.frame(next);
+
+		self.cursor += self.pitch.max(0.0) as f64
+			* self.clip.sampleRate as f64 / sampleRate as f64;
+
+		let v = self.volume * self.attenuation;
+		Some((
+			(a.0 + (b.0 - a.0) * t) * v * (1.0 - self.pan).min(1.0),
+			(a.1 + (b.1 - a.1) * t) * v * (1.0 + self.pan).min(1.0)
+		))
+	}
+}
+
+// Single producer, single consumer queue of samples, neither side ever waits for the other
+struct Ring
+{
+	data: Vec<AtomicU32>,
+	read: AtomicUsize,
+	write: AtomicUsize
+}
+
+impl Ring
+{
+	fn new(size: usize) -> Self
+	{
+		Self
+		{
+			data: (0..size).map(|_| AtomicU32::new(0)).collect(),
+			read: AtomicUsize::new(0),
+			write: AtomicUsize::new(0)
+		}
+	}
+
+	fn len(&self) -> usize
+	{
+		self.write.load(Ordering::Acquire).wrapping_sub(self.read.load(Ordering::Acquire))
+	}
+
+	fn free(&self) -> usize { self.data.len() - self.len() }
+
+	// Called by the feeder only
+	fn push(&self, samples: &[f32])
+	{
+		let w = self.write.load(Ordering::Relaxed);
+		for (i, x) in samples.iter().enumerate()
+		{
+			self.data[(w + i) % self.data.len()].store(x.to_bits(), Ordering::Relaxed);
+		}
+		self.write.store(w.wrapping_add(samples.len()), Ordering::Release);
+	}
+
+	// Called by the audio thread only
+	fn pop(&self, out: &mut [f32]) -> bool
+	{
+		if self.len() < out.len() { return false; }
+		let r = self.read.load(Ordering::Relaxed);
+		for (i, x) in out.iter_mut().enumerate()
+		{
+			*x = f32::from_bits(self.data[(r + i) % self.data.len()].load(Ordering::Relaxed));
+		}
+		self.read.store(r.wrapping_add(out.len()), Ordering::Release);
+		true
+	}
+}
+
+// State shared by a track and its feeder thread
+struct Stream
+{
+	ring: Ring,
+	ended: AtomicBool,
+	stopped: AtomicBool
+}
+
+// Music is decoded by a feeder thread ahead of playback, so the audio thread never reads files
+pub struct Track
+{
+	path: String,
+	stream: Arc<Stream>,
+	frame: [f32; 2],
+	cursor: f64,
+	channels: u16,
+	sampleRate: u32,
+	gain: f32,
+	target: f32,
+	speed: f32
+}
+
+impl Track
+{
+	pub fn open(path: String) -> Option<Self>
+	{
+		let file = std::fs::File::open(&path);
+		if let Err(x) = file
+		{
+			println!("Error on reading music from {path}:\n{x}");
+			return None;
+		}
+		let reader = lewton::inside_ogg::OggStreamReader::new(file.unwrap());
+		if let Err(x) = reader
+		{
+			println!("Error on decoding music from {path}:\n{x}");
+			return None;
+		}
+		let reader = reader.unwrap();
+		let channels = reader.ident_hdr.audio_channels as u16;
+		let sampleRate = reader.ident_hdr.audio_sample_rate;
+
+		// Half a second of audio
+		let stream = Arc::new(Stream
+		{
+			ring: Ring::new((sampleRate as usize * channels.max(1) as usize / 2).max(4096)),
+			ended: AtomicBool::new(false),
+			stopped: AtomicBool::new(false)
+		});
+		let feeder = stream.clone();
+		let name = path.clone();
+		std::thread::spawn(move || Track::feed(reader, feeder, name, channels.max(1) as usize));
+
+		Some(Self
+		{
+			path,
+			stream,
+			frame: [0.0; 2],
+			cursor: 1.0,
+			channels,
+			sampleRate,
+			gain: 0.0,
+			target: 1.0,
+			speed: 0.0
+		})
+	}
+
+	// Music is looped, the thread stops when the track is dropped
+	fn feed(mut reader: lewton::inside_ogg::OggStreamReader<std::fs::File>, stream: Arc<Stream>, path: String, channels: usize)
+	{
+		let mut packet: Vec<f32> = vec![];
+		let mut rewound = false;
+		while !stream.stopped.load(Ordering::Relaxed)
+		{
+			if !packet.is_empty()
+			{
+				let n = packet.len().min(stream.ring.free()) / channels * channels;
+				stream.ring.push(&packet[..n]);
+				packet.drain(..n);
+				if !packet.is_empty()
+				{
+					std::thread::sleep(std::time::Duration::from_millis(10));
+					continue;
+				}
+			}
+
+			match reader.read_dec_packet_itl()
+			{
+				Ok(Some(x)) =>
+				{
+					if !x.is_empty() { rewound = false; }
+					packet.extend(x.iter().map(|x| *x as f32 / 32768.0));
+				}
+				Ok(None) if !rewound =>
+				{
+					if reader.seek_absgp_pg(0).is_err() { break; }
+					rewound = true;
+				}
+				Ok(None) => break,
+				Err(x) =>
+				{
+					println!("Error on decoding music from {path}:\n{x}");
+					break;
+				}
+			}
+		}
+		stream.ended.store(true, Ordering::Release);
+	}
+
+	fn fade(&mut self, target: f32, duration: f32)
+	{
+		self.target = target;
+		if duration <= 0.0 { self.gain = target; self.speed = 0.0; }
+		else { self.speed = 1.0 / duration; }
+	}
+
+	fn sample(&mut self, sampleRate: u32) -> Option<(f32, f32)>
+	{
+		let c = self.channels.max(1) as usize;
+		let mut buf = [0.0; 8];
+		while self.cursor >= 1.0
+		{
+			if c > buf.len() || !self.stream.ring.pop(&mut buf[..c])
+			{
+				// Nothing was decoded yet, silence is played until the feeder catches up
+				if self.stream.ended.load(Ordering::Acquire) && self.stream.ring.len() < c { return None; }
+				return Some((0.0, 0.0));
+			}
+			self.frame = if c == 1 { [buf[0], buf[0]] } else { [buf[0], buf[1]] };
+			self.cursor -= 1.0;
+		}
+
+		self.cursor += self.sampleRate as f64 / sampleRate as f64;
+
+		let step = self.speed / sampleRate as f32;
+		if self.gain < self.target { self.gain = (self.gain + step).min(self.target); }
+		else if self.gain > self.target { self.gain = (self.gain - step).max(self.target); }
+
+		Some((self.frame[0] * self.gain, self.frame[1] * self.gain))
+	}
+
+	fn isFinished(&self) -> bool
+	{
+		self.target <= 0.0 && self.gain <= 0.0
+	}
+}
+
+impl Drop for Track
+{
+	fn drop(&mut self)
+	{
+		self.stream.stopped.store(true, Ordering::Relaxed);
+	}
+}
+
+pub struct Mixer
+{
+	voices: HashMap<u32, Voice>,
+	music: Vec<Track>,
+	nextID: u32,
+	pub volume: f32,
+	pub musicVolume: f32
+}
+
+impl Mixer
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			voices: HashMap::new(),
+			music: vec![],
+			nextID: 1,
+			volume: 1.0,
+			musicVolume: 1.0
+		}
+	}
+
+	pub fn add(&mut self, voice: Voice) -> u32
+	{
+		let id = self.nextID;
+		self.nextID = self.nextID.wrapping_add(1).max(1);
+		self.voices.insert(id, voice);
+		id
+	}
+
+	pub fn getVoice(&mut self, id: u32) -> Option<&mut Voice>
+	{
+		self.voices.get_mut(&id)
+	}
+
+	pub fn remove(&mut self, id: u32)
+	{
+		self.voices.remove(&id);
+	}
+
+	pub fn clear(&mut self)
+	{
+		self.voices.clear();
+	}
+
+	pub fn mix(&mut self, out: &mut [f32], channels: u16, sampleRate: u32)
+	{
+		out.fill(0.0);
+		let c = channels.max(1) as usize;
+		let mut finished = vec![];
+
+		for (id, voice) in &mut self.voices
+		{
+			if voice.paused { continue; }
+			for frame in out.chunks_mut(c)
+			{
+				match voice.sample(sampleRate)
+				{
+					Some((l, r)) =>
+					{
+						if c == 1 { frame[0] += (l + r) / 2.0; continue; }
+						for (i, x) in frame.iter_mut().enumerate()
+						{
+							*x += if i % 2 == 0 { l } else { r };
+						}
+					}
+					None => { finished.push(*id); break; }
+				}
+			}
+		}
+
+		for id in finished { self.voices.remove(&id); }
+
+		for track in &mut self.music
+		{
+			for frame in out.chunks_mut(c)
+			{
+				match track.sample(sampleRate)
+				{
+					Some((l, r)) =>
+					{
+						let (l, r) = (l * self.musicVolume, r * self.musicVolume);
+						if c == 1 { frame[0] += (l + r) / 2.0; continue; }
+						for (i, x) in frame.iter_mut().enumerate()
+						{
+							*x += if i % 2 == 0 { l } else { r };
+						}
+					}
+					None => { track.fade(0.0, 0.0); break; }
+				}
+			}
+		}
+		self.music.retain(|x| !x.isFinished());
+
+		for x in out.iter_mut()
+		{
+			*x = (*x * self.volume).clamp(-1.0, 1.0);
+		}
+	}
+}
+
+pub enum Output
+{
+	Null,
+	Device(cpal::Stream)
+}
+
+pub struct Audio
+{
+	mixer: Arc<Mutex<Mixer>>,
+	clips: HashMap<String, Arc<Clip>>,
+	output: Output,
+	channels: u16,
+	sampleRate: u32,
+	radius: f32,
+	fade: f32
+}
+
+impl Audio
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			mixer: Arc::new(Mutex::new(Mixer::new())),
+			clips: HashMap::new(),
+			output: Output::Null,
+			channels: 2,
+			sampleRate: 44100,
+			radius: 1000.0,
+			fade: 1.0
+		}
+	}
+
+	pub fn load(&mut self)
+	{
+		let device = cpal::default_host().default_output_device();
+		if device.is_none()
+		{
+			println!("No audio output device found, sound is disabled.");
+			return;
+		}
+		let device = device.unwrap();
+
+		let cfg = device.default_output_config();
+		if let Err(x) = cfg
+		{
+			println!("Failed to get audio output config:\n{x}");
+			return;
+		}
+		let cfg = cfg.unwrap();
+
+		self.channels = cfg.channels();
+		self.sampleRate = cfg.sample_rate().0;
+
+		let stream = match cfg.sample_format()
+		{
+			cpal::SampleFormat::I16 => self.buildStream::<i16>(&device, &cfg.config()),
+			cpal::SampleFormat::U16 => self.buildStream::<u16>(&device, &cfg.config()),
+			cpal::SampleFormat::I32 => self.buildStream::<i32>(&device, &cfg.config()),
+			_ => self.buildStream::<f32>(&device, &cfg.config())
+		};
+
+		match stream
+		{
+			Ok(s) =>
+			{
+				if let Err(x) = s.play() { println!("Failed to start audio stream:\n{x}"); }
+				self.output = Output::Device(s);
+			}
+			Err(x) => println!("Failed to open audio stream:\n{x}")
+		}
+	}
+
+	fn buildStream<T>(&self, device: &cpal::Device, cfg: &cpal::StreamConfig)
+		-> Result<cpal::Stream, cpal::BuildStreamError>
+	where T: cpal::SizedSample + cpal::FromSample<f32>
+	{
+		let mixer = self.mixer.clone();
+		let channels = self.channels;
+		let sampleRate = self.sampleRate;
+		let mut buffer = vec![];
+
+		device.build_output_stream(
+			cfg,
+			move |data: &mut [T], _: &cpal::OutputCallbackInfo|
+			{
+				buffer.resize(data.len(), 0.0);
+				if let Ok(mut m) = mixer.lock()
+				{
+					m.mix(&mut buffer, channels, sampleRate);
+				} else { buffer.fill(0.0); }
+				for (x, y) in data.iter_mut().zip(&buffer)
+				{
+					*x = T::from_sample(*y);
+				}
+			},
+			|x| println!("Audio stream error: {x}"),
+			None
+		)
+	}
+
+	pub fn update(&mut self)
+	{
+		self.mixer.lock().unwrap().musicVolume = Window::getInstance().prog
+			.get("musicVolume").map(|x| x.num).unwrap_or(1.0).max(0.0);
+
+		for v in self.mixer.lock().unwrap().voices.values_mut()
+		{
+			Audio::spatialize(v);
+		}
+	}
+
+	// Pan and attenuation of a positional voice relative to the centre of the screen
+	fn spatialize(v: &mut Voice)
+	{
+		let pos = match v.position { Some(p) => p, None => return };
+
+		let cam = Window::getCamera();
+		let s = Window::getSize();
+		let listener = cam.screenToWorld(glam::vec2(s.0 as f32, s.1 as f32) / 2.0);
+
+		let x = cam.worldToScreen(pos).x;
+		v.pan = if s.0 > 0 { (x / s.0 as f32 * 2.0 - 1.0).clamp(-1.0, 1.0) } else { 0.0 };
+
+		v.attenuation = if v.radius > 0.0
+		{
+			(1.0 - pos.distance(listener) / v.radius).clamp(0.0, 1.0).powi(2)
+		} else { 1.0 };
+	}
+
+	pub fn render(&mut self, out: &mut [f32])
+	{
+		self.mixer.lock().unwrap().mix(out, self.channels, self.sampleRate);
+	}
+
+	pub fn isNull(&self) -> bool
+	{
+		matches!(self.output, Output::Null)
+	}
+
+	pub fn getClip(&mut self, path: String) -> Option<Arc<Clip>>
+	{
+		if let Some(c) = self.clips.get(&path) { return Some(c.clone()); }
+
+		let clip = Arc::new(Clip::load(path.clone())?);
+		self.clips.insert(path, clip.clone());
+		Some(clip)
+	}
+
+	pub fn play(&mut self, path: String) -> u32
+	{
+		self.playVoice(path, None)
+	}
+
+	// The voice is placed before it's mixed, so it doesn't start at full volume in the centre
+	pub fn playAt(&mut self, path: String, position: glam::Vec2) -> u32
+	{
+		self.playVoice(path, Some(position))
+	}
+
+	fn playVoice(&mut self, path: String, position: Option<glam::Vec2>) -> u32
+	{
+		match self.getClip(path)
+		{
+			Some(c) =>
+			{
+				let mut v = Voice::new(c);
+				v.radius = self.radius;
+				v.position = position;
+				Audio::spatialize(&mut v);
+				self.mixer.lock().unwrap().add(v)
+			}
+			None => 0
+		}
+	}
+
+	pub fn modify(&mut self, id: u32, func: impl FnOnce(&mut Voice))
+	{
+		if let Some(v) = self.mixer.lock().unwrap().getVoice(id)
+		{
+			func(v);
+		}
+	}
+
+	pub fn isPlaying(&self, id: u32) -> bool
+	{
+		self.mixer.lock().unwrap().voices.contains_key(&id)
+	}
+
+	pub fn stop(&mut self, id: u32)
+	{
+		self.mixer.lock().unwrap().remove(id);
+	}
+
+	pub fn stopAll(&mut self)
+	{
+		self.mixer.lock().unwrap().clear();
+	}
+
+	pub fn setVolume(&mut self, volume: f32)
+	{
+		self.mixer.lock().unwrap().volume = volume.max(0.0);
+	}
+
+	pub fn getVolume(&self) -> f32
+	{
+		self.mixer.lock().unwrap().volume
+	}
+
+	pub fn setRadius(&mut self, radius: f32)
+	{
+		self.radius = radius.max(0.0);
+	}
+
+	pub fn getRadius(&self) -> f32 { self.radius }
+
+	pub fn playMusic(&mut self, path: String, fade: Option<f32>)
+	{
+		let fade = fade.unwrap_or(self.fade);
+
+		if let Some(t) = self.mixer.lock().unwrap().music.last_mut()
+		{
+			if t.path == path && t.target > 0.0
+			{
+				t.fade(1.0, fade);
+				return;
+			}
+		}
+
+		// The file is opened before locking, so the audio thread doesn't wait for it
+		let track = Track::open(path);
+		let mut m = self.mixer.lock().unwrap();
+		for t in &mut m.music { t.fade(0.0, fade); }
+		if let Some(mut t) = track
+		{
+			t.gain = 0.0;
+			t.fade(1.0, fade);
+			m.music.push(t);
+		}
+	}
+
+	pub fn stopMusic(&mut self, fade: Option<f32>)
+	{
+		let fade = fade.unwrap_or(self.fade);
+		for t in &mut self.mixer.lock().unwrap().music { t.fade(0.0, fade); }
+	}
+
+	pub fn getMusic(&self) -> String
+	{
+		match self.mixer.lock().unwrap().music.last()
+		{
+			Some(t) if t.target > 0.0 => t.path.clone(),
+			_ => String::new()
+		}
+	}
+
+	pub fn setMusicFade(&mut self, fade: f32)
+	{
+		self.fade = fade.max(0.0);
+	}
+
+	pub fn getMusicFade(&self) -> f32 { self.fade }
+
+	pub fn clearCache(&mut self)
+	{
+		self.clips.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests
+{
+	use super::*;
+
+	const RATE: u32 = 100;
+
+	fn clip(samples: Vec<f32>, channels: u16) -> Arc<Clip>
+	{
+		Arc::new(Clip { samples, channels, sampleRate: RATE })
+	}
+
+	fn mix(m: &mut Mixer, frames: usize, channels: u16) -> Vec<f32>
+	{
+		let mut out = vec![0.0; frames * channels as usize];
+		m.mix(&mut out, channels, RATE);
+		out
+	}
+
+	fn assertNear(a: &[f32], b: &[f32])
+	{
+		assert_eq!(a.len(), b.len());
+		for (x, y) in a.iter().zip(b) { assert!((x - y).abs() < 1e-5, "{a:?} != {b:?}"); }
+	}
+
+	#[test]
+	fn mixesTwoVoices()
+	{
+		let mut m = Mixer::new();
+		let mut a = Voice::new(clip(vec![0.25; 4], 1));
+		a.looping = true;
+		let mut b = Voice::new(clip(vec![0.5, -0.5], 2));
+		b.looping = true;
+		m.add(a);
+		m.add(b);
+
+		assertNear(&mix(&mut m, 2, 2), &[0.75, -0.25, 0.75, -0.25]);
+	}
+
+	#[test]
+	fn loopingWrapsAround()
+	{
+		let mut m = Mixer::new();
+		let mut v = Voice::new(clip(vec![0.1, 0.2, 0.3], 1));
+		v.looping = true;
+		let id = m.add(v);
+
+		assertNear(&mix(&mut m, 5, 1), &[0.1, 0.2, 0.3, 0.1, 0.2]);
+		assert!(m.getVoice(id).is_some());
+	}
+
+	#[test]
+	fn pitchResamplesClip()
+	{
+		let mut m = Mixer::new();
+		let mut v = Voice::new(clip(vec![0.0, 0.2, 0.4, 0.6], 1));
+		v.pitch = 0.5;
+		m.add(v);
+
+		assertNear(&mix(&mut m, 4, 1), &[0.0, 0.1, 0.2, 0.3]);
+	}
+
+	#[test]
+	fn appliesMasterAndMusicVolume()
+	{
+		let mut m = Mixer::new();
+		let mut v = Voice::new(clip(vec![0.5; 8], 1));
+		v.looping = true;
+		m.add(v);
+
+		let stream = Arc::new(Stream
+		{
+			ring: Ring::new(16),
+			ended: AtomicBool::new(false),
+			stopped: AtomicBool::new(true)
+		});
+		stream.ring.push(&[0.4; 16]);
+		let mut t = Track
+		{
+			path: String::new(),
+			stream,
+			frame: [0.0; 2],
+			cursor: 1.0,
+			channels: 2,
+			sampleRate: RATE,
+			gain: 0.0,
+			target: 1.0,
+			speed: 0.0
+		};
+		t.fade(1.0, 0.0);
+		m.music.push(t);
+
+		m.volume = 0.5;
+		m.musicVolume = 0.5;
+		// (0.5 + 0.4 * 0.5) * 0.5
+		assertNear(&mix(&mut m, 2, 2), &[0.35; 4]);
+	}
+
+	#[test]
+	fn removesFinishedVoices()
+	{
+		let mut m = Mixer::new();
+		let id = m.add(Voice::new(clip(vec![0.5, 0.5], 1)));
+
+		assertNear(&mix(&mut m, 4, 1), &[0.5, 0.5, 0.0, 0.0]);
+		assert!(m.getVoice(id).is_none());
+	}
+}
@@ -11,6 +11,8 @@ pub struct Camera
 {
 	ts: Transformable2D,
 	fbo: u32,
+	// Size of the framebuffer textures, it follows the window size and not the world size
+	fboSize: (i32, i32),
 	sbuf: u32,
 	tex: u32,
 	vao: u32,
@@ -20,7 +22,9 @@ pub struct Camera
 	size: glam::Vec2,
 	useTS: bool,
 	uniVAO: bool,
-	activeShader: String
+	activeShader: String,
+	parent: glam::Mat4,
+	opacity: f32
 }
 
 impl Camera
@@ -31,6 +35,7 @@ impl Camera
 		{
 			ts: Transformable2D::new(),
 			fbo: 0,
+			fboSize: (0, 0),
 			sbuf: 0,
 			tex: 0,
 			vao: 0,
@@ -40,13 +45,16 @@ impl Camera
 			size: glam::Vec2::ZERO,
 			useTS: false,
 			uniVAO: false,
-			activeShader: String::new()
+			activeShader: String::new(),
+			parent: glam::Mat4::IDENTITY,
+			opacity: 1.0
 		}
 	}
 
 	pub fn load(&mut self)
 	{
 		let (w, h) = Window::getSize();
+		self.fboSize = (w, h);
 
 		unsafe
 		{
@@ -143,6 +151,11 @@ impl Camera
 		Window::getProfiler().save("render".to_string());
 	}
 
+	pub fn capture(&mut self) -> (i32, i32, Vec<u8>)
+	{
+		Window::readPixels(self.fbo, self.fboSize)
+	}
+
 	pub fn toggleTransform(&mut self, enable: bool)
 	{
 		self.useTS = enable;
@@ -167,6 +180,7 @@ impl Camera
 		else
 		{
 			self.uiProj = m;
+			self.fboSize = s;
 			unsafe
 			{
 				gl::BindTexture(gl::TEXTURE_2D, self.tex);
@@ -214,6 +228,22 @@ impl Camera
 		self.size
 	}
 
+	pub fn screenToWorld(&mut self, pos: glam::Vec2) -> glam::Vec2
+	{
+		let a = self.ts.getPosition();
+		let s = Window::getSize();
+		let k = glam::vec2(self.size.x / s.0 as f32, self.size.y / s.1 as f32);
+		-a - self.size / 2.0 + pos * k
+	}
+
+	pub fn worldToScreen(&mut self, pos: glam::Vec2) -> glam::Vec2
+	{
+		let p = self.ts.getMatrix().transform_point3(glam::vec3(pos.x, pos.y, 0.0));
+		let s = Window::getSize();
+		let k = glam::vec2(s.0 as f32 / self.size.x, s.1 as f32 / self.size.y);
+		glam::vec2(p.x * k.x, p.y * k.y)
+	}
+
 	pub fn getBounds(&mut self) -> glam::Vec4
 	{
 		let s =
@@ -241,6 +271,34 @@ impl Camera
 		s
 	}
 
+	// Forces the next activateShader call to bind the program, used after shaders are reloaded
+	pub fn resetShader(&mut self) { self.activeShader.clear(); }
+
+	// Transform and opacity of the UI object being drawn, applied on top of the model matrix
+	pub fn setParent(&mut self, m: glam::Mat4, opacity: f32)
+	{
+		self.parent = m;
+		self.opacity = opacity;
+	}
+
+	pub fn getParent(&self) -> glam::Mat4 { self.parent }
+
+	pub fn getOpacity(&self) -> f32 { self.opacity }
+
+	pub fn transformRect(&self, r: glam::Vec4) -> glam::Vec4
+	{
+		if self.parent == glam::Mat4::IDENTITY { return r; }
+		let m = self.parent;
+		let p1 = m * glam::vec4(r.x, r.y, 0.0, 1.0);
+		let p2 = m * glam::vec4(r.x + r.z, r.y, 0.0, 1.0);
+		let p3 = m * glam::vec4(r.x + r.z, r.y + r.w, 0.0, 1.0);
+		let p4 = m * glam::vec4(r.x, r.y + r.w, 0.0, 1.0);
+
+		let min = p1.min(p2).min(p3).min(p4);
+		let max = p1.max(p2).max(p3).max(p4);
+		glam::vec4(min.x, min.y, max.x - min.x, max.y - min.y)
+	}
+
 	pub fn isVisible(&mut self, h2: glam::Vec4) -> bool
 	{
 		let h1 = if self.useTS { self.getBounds() }
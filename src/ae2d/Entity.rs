@@ -34,6 +34,11 @@ impl Entity
 		bind::shapes(&ent.script);
 		bind::shaders(&ent.script);
 		bind::skeleton(&ent.script);
+		bind::audio(&ent.script);
+		bind::input(&ent.script);
+		bind::gamepad(&ent.script);
+		bind::loc(&ent.script);
+		bind::resources(&ent.script);
 
 		let _ = ent.script.load(
 			std::fs::read_to_string(
@@ -0,0 +1,194 @@
+use crate::ae2d::Window::Window;
+
+const BUTTONS: [&str; 15] = [
+	"A", "B", "X", "Y", "LB", "RB", "Back", "Start", "Guide", "LS", "RS",
+	"DpadUp", "DpadRight", "DpadDown", "DpadLeft"
+];
+
+const AXES: [&str; 6] = [
+	"LeftX", "LeftY", "RightX", "RightY", "LT", "RT"
+];
+
+struct Pad
+{
+	connected: bool,
+	name: String,
+	buttons: [bool; 15],
+	prevButtons: [bool; 15],
+	// Buttons at the previous and the last world tick in fixed-step mode
+	tickPrev: [bool; 15],
+	tickLast: [bool; 15],
+	axes: [f32; 6]
+}
+
+impl Pad
+{
+	fn new() -> Self
+	{
+		Self
+		{
+			connected: false,
+			name: String::new(),
+			buttons: [false; 15],
+			prevButtons: [false; 15],
+			tickPrev: [false; 15],
+			tickLast: [false; 15],
+			axes: [0.0; 6]
+		}
+	}
+
+	fn previous(&self) -> &[bool; 15]
+	{
+		if Window::isTicking() { &self.tickPrev } else { &self.prevButtons }
+	}
+}
+
+pub struct Gamepad
+{
+	pads: [Pad; 4],
+	deadZone: f32
+}
+
+impl Gamepad
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			pads: std::array::from_fn(|_| Pad::new()),
+			deadZone: 0.2
+		}
+	}
+
+	pub fn update(&mut self, context: &glfw::Glfw)
+	{
+		let dz = self.deadZone;
+		for (i, pad) in self.pads.iter_mut().enumerate()
+		{
+			let j = context.get_joystick(glfw::JoystickId::from_i32(i as i32).unwrap());
+			let state = if j.is_gamepad() { j.get_gamepad_state() } else { None };
+
+			let connected = state.is_some();
+			if connected != pad.connected
+			{
+				pad.connected = connected;
+				pad.name = j.get_gamepad_name().unwrap_or_default();
+				if connected { println!("Gamepad #{} connected: {}", i + 1, pad.name); }
+				else { println!("Gamepad #{} disconnected", i + 1); }
+				Window::getUI().gamepadEvent(i as u8 + 1, connected);
+			}
+
+			pad.prevButtons = pad.buttons;
+			match state
+			{
+				Some(s) =>
+				{
+					for (b, x) in pad.buttons.iter_mut().enumerate()
+					{
+						*x = s.get_button_state(
+							glfw::GamepadButton::from_i32(b as i32).unwrap()
+						) == glfw::Action::Press;
+					}
+					for (a, x) in pad.axes.iter_mut().enumerate()
+					{
+						let v = s.get_axis(glfw::GamepadAxis::from_i32(a as i32).unwrap());
+						*x = if a < 4 { Gamepad::applyDeadZone(v, dz) }
+							else { Gamepad::applyDeadZone((v + 1.0) / 2.0, dz) };
+					}
+				}
+				None =>
+				{
+					pad.buttons = [false; 15];
+					pad.axes = [0.0; 6];
+				}
+			}
+		}
+	}
+
+	pub fn latch(&mut self)
+	{
+		for pad in &mut self.pads
+		{
+			pad.tickPrev = pad.tickLast;
+			pad.tickLast = pad.buttons;
+		}
+	}
+
+	fn applyDeadZone(v: f32, dz: f32) -> f32
+	{
+		if v.abs() < dz { return 0.0; }
+		v.signum() * ((v.abs() - dz) / (1.0 - dz)).min(1.0)
+	}
+
+	fn getPad(&self, id: u8) -> Option<&Pad>
+	{
+		if id == 0 { return None; }
+		self.pads.get(id as usize - 1).filter(|x| x.connected)
+	}
+
+	fn buttonID(name: &str) -> Option<usize>
+	{
+		BUTTONS.iter().position(|x| *x == name)
+	}
+
+	fn axisID(name: &str) -> Option<usize>
+	{
+		AXES.iter().position(|x| *x == name)
+	}
+
+	pub fn isConnected(&self, id: u8) -> bool { self.getPad(id).is_some() }
+
+	pub fn getName(&self, id: u8) -> String
+	{
+		self.getPad(id).map(|x| x.name.clone()).unwrap_or_default()
+	}
+
+	pub fn isPressed(&self, id: u8, button: &str) -> bool
+	{
+		match (self.getPad(id), Gamepad::buttonID(button))
+		{
+			(Some(p), Some(b)) => p.buttons[b],
+			_ => false
+		}
+	}
+
+	pub fn isJustPressed(&self, id: u8, button: &str) -> bool
+	{
+		match (self.getPad(id), Gamepad::buttonID(button))
+		{
+			(Some(p), Some(b)) => p.buttons[b] && !p.previous()[b],
+			_ => false
+		}
+	}
+
+	pub fn isJustReleased(&self, id: u8, button: &str) -> bool
+	{
+		match (self.getPad(id), Gamepad::buttonID(button))
+		{
+			(Some(p), Some(b)) => !p.buttons[b] && p.previous()[b],
+			_ => false
+		}
+	}
+
+	pub fn getAxis(&self, id: u8, axis: &str) -> f32
+	{
+		match (self.getPad(id), Gamepad::axisID(axis))
+		{
+			(Some(p), Some(a)) => p.axes[a],
+			_ => 0.0
+		}
+	}
+
+	// "A", "LT", "LeftX+" or "LeftY-", the sign selects half of a stick axis
+	pub fn bindingValue(&self, id: u8, name: &str) -> f32
+	{
+		if let Some(a) = name.strip_suffix('+') { return self.getAxis(id, a).max(0.0); }
+		if let Some(a) = name.strip_suffix('-') { return (-self.getAxis(id, a)).max(0.0); }
+		if Gamepad::axisID(name).is_some() { return self.getAxis(id, name).abs(); }
+		self.isPressed(id, name) as u8 as f32
+	}
+
+	pub fn setDeadZone(&mut self, dz: f32) { self.deadZone = dz.clamp(0.0, 0.95); }
+
+	pub fn getDeadZone(&self) -> f32 { self.deadZone }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use crate::ae2d::Window::{Event, Window};
+
+pub struct Input
+{
+	path: String,
+	defaults: HashMap<String, Vec<String>>,
+	actions: HashMap<String, Vec<String>>,
+	axes: HashMap<String, (String, String)>,
+	state: HashMap<String, (bool, bool)>,
+	// Same as state, but taken once per world tick in fixed-step mode
+	ticks: HashMap<String, (bool, bool)>
+}
+
+impl Input
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			path: String::new(),
+			defaults: HashMap::new(),
+			actions: HashMap::new(),
+			axes: HashMap::new(),
+			state: HashMap::new(),
+			ticks: HashMap::new()
+		}
+	}
+
+	fn parseActions(node: &json::JsonValue) -> HashMap<String, Vec<String>>
+	{
+		let mut actions = HashMap::new();
+		for (name, list) in node.entries()
+		{
+			actions.insert(
+				name.to_string(),
+				list.members()
+					.filter_map(|x| x.as_str())
+					.map(|x| x.to_string())
+					.collect()
+			);
+		}
+		actions
+	}
+
+	pub fn load(&mut self, path: String)
+	{
+		self.path = path.clone();
+		self.defaults.clear();
+		self.actions.clear();
+		self.axes.clear();
+		self.state.clear();
+		self.ticks.clear();
+
+		let src = std::fs::read_to_string(&path);
+		if src.is_err() { return; }
+		let src = json::parse(&src.unwrap());
+		if let Err(x) = src
+		{
+			println!("Failed to load input map from {path}: {x}");
+			return;
+		}
+		let src = src.unwrap();
+
+		for (section, value) in src.entries()
+		{
+			if section == "actions"
+			{
+				self.defaults = Input::parseActions(value);
+			}
+			if section == "axes"
+			{
+				for (name, pair) in value.entries()
+				{
+					self.axes.insert(name.to_string(), (
+						pair[0].as_str().unwrap_or_default().to_string(),
+						pair[1].as_str().unwrap_or_default().to_string()
+					));
+				}
+			}
+		}
+
+		self.actions = self.defaults.clone();
+
+		if let Ok(src) = json::parse(
+			&std::fs::read_to_string(self.getUserPath()).unwrap_or_default()
+		)
+		{
+			for (name, list) in Input::parseActions(&src)
+			{
+				self.actions.insert(name, list);
+			}
+		}
+	}
+
+	fn getUserPath(&self) -> String
+	{
+		match self.path.strip_suffix(".json")
+		{
+			Some(x) => String::from(x) + ".user.json",
+			None => self.path.clone() + ".user"
+		}
+	}
+
+	pub fn save(&self)
+	{
+		let mut obj = json::object!{};
+		for (name, list) in &self.actions
+		{
+			if self.defaults.get(name) == Some(list) { continue; }
+			let _ = obj.insert(name, list.clone());
+		}
+
+		let path = self.getUserPath();
+		if let Err(x) = std::fs::write(&path, obj.pretty(4))
+		{
+			println!("Failed to save input map to {path}: {x}");
+		}
+	}
+
+	pub fn reset(&mut self)
+	{
+		self.actions = self.defaults.clone();
+	}
+
+	pub fn update(&mut self)
+	{
+		for (name, list) in &self.actions
+		{
+			let pressed = list.iter().any(|x| Input::bindingValue(x) > 0.5);
+			let s = self.state.entry(name.clone()).or_insert((false, false));
+			*s = (s.1, pressed);
+		}
+	}
+
+	// An edge is seen by the first tick after it, even if no tick runs in that frame
+	pub fn latch(&mut self)
+	{
+		for (name, s) in &self.state
+		{
+			let t = self.ticks.entry(name.clone()).or_insert((false, false));
+			*t = (t.1, s.1);
+		}
+	}
+
+	fn edges(&self) -> &HashMap<String, (bool, bool)>
+	{
+		if Window::isTicking() { &self.ticks } else { &self.state }
+	}
+
+	pub fn bindingValue(name: &str) -> f32
+	{
+		let w = Window::getInstance().window.as_ref().unwrap();
+		if let Some(b) = name.strip_prefix("Gamepad")
+		{
+			let (id, button) = b.split_once('.').unwrap_or(("", b));
+			return Window::getGamepad().bindingValue(id.parse().unwrap_or(1), button);
+		}
+		if let Some(b) = name.strip_prefix("Mouse.")
+		{
+			return (w.get_mouse_button(Window::strToMB(b.to_string())) == glfw::Action::Press) as u8 as f32;
+		}
+		let key = Window::strToKey(name.to_string());
+		if key == glfw::Key::Unknown { return 0.0; }
+		(w.get_key(key) == glfw::Action::Press) as u8 as f32
+	}
+
+	pub fn value(&self, action: &str) -> f32
+	{
+		match self.actions.get(action)
+		{
+			Some(list) => list.iter()
+				.map(|x| Input::bindingValue(x))
+				.fold(0.0, f32::max),
+			None => 0.0
+		}
+	}
+
+	pub fn isPressed(&self, action: &str) -> bool
+	{
+		self.state.get(action).is_some_and(|x| x.1)
+	}
+
+	pub fn isJustPressed(&self, action: &str) -> bool
+	{
+		self.edges().get(action).is_some_and(|x| x.1 && !x.0)
+	}
+
+	pub fn isJustReleased(&self, action: &str) -> bool
+	{
+		self.edges().get(action).is_some_and(|x| !x.1 && x.0)
+	}
+
+	pub fn axis(&self, name: &str) -> f32
+	{
+		match self.axes.get(name)
+		{
+			Some((neg, pos)) => (self.value(pos) - self.value(neg)).clamp(-1.0, 1.0),
+			None => 0.0
+		}
+	}
+
+	pub fn getBindings(&self, action: &str) -> Vec<String>
+	{
+		self.actions.get(action).cloned().unwrap_or_default()
+	}
+
+	pub fn setBindings(&mut self, action: String, list: Vec<String>)
+	{
+		self.actions.insert(action, list);
+	}
+
+	pub fn lastPressed() -> String
+	{
+		for e in &Window::getInstance().eventQueue
+		{
+			let name = match e
+			{
+				Event::Key(k, glfw::Action::Press, _) => Window::keyToStr(*k),
+				Event::Mouse(b, glfw::Action::Press, _) =>
+				{
+					let name = Window::mbToStr(*b);
+					if name.is_empty() { continue; }
+					String::from("Mouse.") + &name
+				}
+				_ => continue
+			};
+			if !name.is_empty() { return name; }
+		}
+		String::new()
+	}
+}
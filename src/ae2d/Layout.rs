@@ -0,0 +1,184 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum Length
+{
+	Auto,
+	Pixels(f32),
+	Percent(f32)
+}
+
+impl Length
+{
+	// 32, "32", "50%" or "auto"
+	pub fn parse(node: &json::JsonValue) -> Self
+	{
+		match node.as_f32()
+		{
+			Some(x) => Length::Pixels(x),
+			None => Length::fromStr(node.as_str().unwrap_or("auto"))
+		}
+	}
+
+	pub fn fromStr(s: &str) -> Self
+	{
+		let s = s.trim();
+		if let Some(p) = s.strip_suffix('%')
+		{
+			return p.trim().parse().map(Length::Percent).unwrap_or(Length::Auto);
+		}
+		s.parse().map(Length::Pixels).unwrap_or(Length::Auto)
+	}
+
+	pub fn resolve(&self, full: f32, scale: f32, auto: f32) -> f32
+	{
+		match self
+		{
+			Length::Auto => auto,
+			Length::Pixels(x) => x * scale,
+			Length::Percent(x) => x / 100.0 * full
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Container
+{
+	None,
+	HStack,
+	VStack,
+	Grid(usize)
+}
+
+pub struct Layout
+{
+	pub anchor: glam::Vec2,
+	pub pivot: glam::Vec2,
+	pub offset: (Length, Length),
+	pub size: (Length, Length),
+	pub margin: glam::Vec4,
+	pub padding: glam::Vec4,
+	pub container: Container,
+	pub spacing: f32,
+	pub cellHeight: Length,
+	pub items: Vec<String>,
+	pub rect: glam::Vec4
+}
+
+fn parsePoint(node: &json::JsonValue) -> glam::Vec2
+{
+	if let Some(s) = node.as_str()
+	{
+		let x = if s.contains("Left") || s == "left" { 0.0 }
+			else if s.contains("Right") || s == "right" { 1.0 } else { 0.5 };
+		let y = if s.starts_with("top") { 0.0 }
+			else if s.starts_with("bottom") { 1.0 } else { 0.5 };
+		return glam::vec2(x, y);
+	}
+	glam::vec2(node[0].as_f32().unwrap_or(0.0), node[1].as_f32().unwrap_or(0.0))
+}
+
+// A single number for every side or [left, top, right, bottom]
+fn parseSides(node: &json::JsonValue) -> glam::Vec4
+{
+	if let Some(x) = node.as_f32() { return glam::Vec4::splat(x); }
+	glam::vec4(
+		node[0].as_f32().unwrap_or(0.0),
+		node[1].as_f32().unwrap_or(0.0),
+		node[2].as_f32().unwrap_or(0.0),
+		node[3].as_f32().unwrap_or(0.0)
+	)
+}
+
+impl Layout
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			anchor: glam::Vec2::ZERO,
+			pivot: glam::Vec2::ZERO,
+			offset: (Length::Pixels(0.0), Length::Pixels(0.0)),
+			size: (Length::Auto, Length::Auto),
+			margin: glam::Vec4::ZERO,
+			padding: glam::Vec4::ZERO,
+			container: Container::None,
+			spacing: 0.0,
+			cellHeight: Length::Auto,
+			items: vec![],
+			rect: glam::Vec4::ZERO
+		}
+	}
+
+	pub fn parse(node: &json::JsonValue) -> Self
+	{
+		let mut l = Self::new();
+		let mut pivot = None;
+
+		for (x, y) in node.entries()
+		{
+			if x == "anchor" { l.anchor = parsePoint(y); }
+			if x == "pivot" { pivot = Some(parsePoint(y)); }
+			if x == "position" { l.offset = (Length::parse(&y[0]), Length::parse(&y[1])); }
+			if x == "size" { l.size = (Length::parse(&y[0]), Length::parse(&y[1])); }
+			if x == "margin" { l.margin = parseSides(y); }
+			if x == "padding" { l.padding = parseSides(y); }
+			if x == "spacing" { l.spacing = y.as_f32().unwrap_or(0.0); }
+			if x == "cellHeight" { l.cellHeight = Length::parse(y); }
+			if x == "columns" { l.container = Container::Grid(y.as_usize().unwrap_or(1).max(1)); }
+			if x == "type"
+			{
+				l.container = match y.as_str().unwrap_or_default()
+				{
+					"hstack" => Container::HStack,
+					"vstack" => Container::VStack,
+					"grid" => Container::Grid(match l.container { Container::Grid(c) => c, _ => 1 }),
+					_ => Container::None
+				};
+			}
+			if x == "items"
+			{
+				l.items = y.members()
+					.filter_map(|x| x.as_str())
+					.map(|x| x.to_string())
+					.collect();
+			}
+		}
+
+		l.pivot = pivot.unwrap_or(l.anchor);
+		l
+	}
+
+	// Places the object inside of the parent rectangle, content is the size used for "auto"
+	pub fn resolve(&mut self, parent: glam::Vec4, scale: f32, content: glam::Vec2)
+	{
+		let m = self.margin * scale;
+		let area = glam::vec4(
+			parent.x + m.x, parent.y + m.y,
+			parent.z - m.x - m.z, parent.w - m.y - m.w
+		);
+		let w = self.size.0.resolve(area.z, scale, content.x);
+		let h = self.size.1.resolve(area.w, scale, content.y);
+		let x = area.x + self.anchor.x * area.z + self.offset.0.resolve(area.z, scale, 0.0) - self.pivot.x * w;
+		let y = area.y + self.anchor.y * area.w + self.offset.1.resolve(area.w, scale, 0.0) - self.pivot.y * h;
+		self.rect = glam::vec4(x, y, w, h);
+	}
+
+	// Space inside of the padding, where items of containers are placed
+	pub fn content(&self, scale: f32) -> glam::Vec4
+	{
+		let p = self.padding * scale;
+		glam::vec4(
+			self.rect.x + p.x, self.rect.y + p.y,
+			self.rect.z - p.x - p.z, self.rect.w - p.y - p.w
+		)
+	}
+
+	// Outer size of the object including margins, used by stacks
+	pub fn outer(&self, scale: f32, full: glam::Vec2, content: glam::Vec2) -> glam::Vec2
+	{
+		let m = self.margin * scale;
+		glam::vec2(
+			self.size.0.resolve(full.x, scale, content.x) + m.x + m.z,
+			self.size.1.resolve(full.y, scale, content.y) + m.y + m.w
+		)
+	}
+}
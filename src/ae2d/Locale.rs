@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+pub struct Locale
+{
+	dir: String,
+	code: String,
+	strings: HashMap<String, String>
+}
+
+impl Locale
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			dir: String::from("res/lang"),
+			code: String::new(),
+			strings: HashMap::new()
+		}
+	}
+
+	pub fn setDir(&mut self, dir: String) { self.dir = dir; }
+
+	// Nested objects are flattened into dotted keys: { "menu": { "play": "..." } } -> "menu.play"
+	fn flatten(&mut self, prefix: String, node: &json::JsonValue)
+	{
+		for (name, value) in node.entries()
+		{
+			let key = if prefix.is_empty() { name.to_string() } else { prefix.clone() + "." + name };
+			if value.is_object() { self.flatten(key, value); }
+			else if let Some(s) = value.as_str() { self.strings.insert(key, s.to_string()); }
+		}
+	}
+
+	pub fn load(&mut self, code: String) -> bool
+	{
+		let path = format!("{}/{code}.json", self.dir);
+		let src = match std::fs::read_to_string(&path)
+		{
+			Ok(x) => x,
+			Err(_) => return false
+		};
+		let src = match json::parse(&src)
+		{
+			Ok(x) => x,
+			Err(x) => { println!("Failed to load language from {path}: {x}"); return false; }
+		};
+
+		self.strings.clear();
+		self.flatten(String::new(), &src);
+		self.code = code;
+		true
+	}
+
+	pub fn getLanguage(&self) -> String { self.code.clone() }
+
+	pub fn has(&self, key: &str) -> bool { self.strings.contains_key(key) }
+
+	// Missing keys are returned as is, "{0}", "{1}"... are replaced with arguments
+	pub fn get(&self, key: &str, args: &[String]) -> String
+	{
+		let template = self.strings.get(key).map(String::as_str).unwrap_or(key);
+
+		// Arguments are copied as is, so "{1}" inside of an argument isn't replaced
+		let mut s = String::with_capacity(template.len());
+		let mut rest = template;
+		while let Some(start) = rest.find('{')
+		{
+			s.push_str(&rest[..start]);
+			rest = &rest[start..];
+			let arg = rest[1..].find('}')
+				.and_then(|end| rest[1..end + 1].parse::<usize>().ok().map(|i| (i, end + 2)))
+				.and_then(|(i, len)| args.get(i).map(|x| (x, len)));
+			match arg
+			{
+				Some((x, len)) => { s.push_str(x); rest = &rest[len..]; }
+				None => { s.push('{'); rest = &rest[1..]; }
+			}
+		}
+		s.push_str(rest);
+		s
+	}
+
+	// "@key" is looked up in the table, "@@" is a literal "@", other strings are kept as is
+	pub fn resolve(&self, s: &str) -> String
+	{
+		if let Some(x) = s.strip_prefix("@@") { return String::from("@") + x; }
+		match s.strip_prefix('@')
+		{
+			Some(key) if self.has(key) => self.get(key, &[]),
+			_ => s.to_string()
+		}
+	}
+}
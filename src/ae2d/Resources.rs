@@ -0,0 +1,471 @@
+use std::{collections::{HashMap, HashSet}, marker::PhantomData, rc::Rc, sync::mpsc::{self, Receiver, Sender}};
+
+use stb_image::image::{Image, LoadResult};
+
+use crate::ae2d::{Shader::Shader, Text::Font, Window::Window};
+
+pub struct Texture
+{
+	id: u32,
+	size: glam::Vec2
+}
+
+impl Texture
+{
+	fn new(data: &Image<u8>) -> Self
+	{
+		let mut t = Self { id: 0, size: glam::Vec2::ZERO };
+		unsafe { gl::GenTextures(1, &mut t.id); }
+		t.upload(data);
+		unsafe
+		{
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+		}
+		t
+	}
+
+	fn upload(&mut self, data: &Image<u8>)
+	{
+		self.size = glam::vec2(data.width as f32, data.height as f32);
+		unsafe
+		{
+			gl::BindTexture(gl::TEXTURE_2D, self.id);
+			gl::TexImage2D(
+				gl::TEXTURE_2D,
+				0,
+				gl::RGBA as i32,
+				data.width as i32,
+				data.height as i32,
+				0,
+				gl::RGBA,
+				gl::UNSIGNED_BYTE,
+				data.data.as_ptr() as *const _
+			);
+		}
+	}
+
+	pub fn getId(&self) -> u32 { self.id }
+
+	pub fn getSize(&self) -> glam::Vec2 { self.size }
+}
+
+impl Drop for Texture
+{
+	fn drop(&mut self)
+	{
+		unsafe { gl::DeleteTextures(1, &self.id); }
+	}
+}
+
+// Doesn't touch GL, so it can run on the worker thread
+fn decode(path: &str) -> Result<Image<u8>, String>
+{
+	match stb_image::image::load(path)
+	{
+		LoadResult::ImageU8(data) => Ok(data),
+		LoadResult::ImageF32(_) => Err(String::from("unable to read F32 type.")),
+		LoadResult::Error(s) => Err(s)
+	}
+}
+
+// Path and image of a texture decoded by the worker
+type Decoded = (String, Result<Image<u8>, String>);
+
+pub trait Resource: Sized + 'static
+{
+	fn storage() -> &'static mut Storage<Self>;
+}
+
+impl Resource for Texture
+{
+	fn storage() -> &'static mut Storage<Self> { &mut Window::getResources().textures }
+}
+
+impl Resource for Shader
+{
+	fn storage() -> &'static mut Storage<Self> { &mut Window::getResources().shaders }
+}
+
+impl Resource for Font
+{
+	fn storage() -> &'static mut Storage<Self> { &mut Window::getResources().fonts }
+}
+
+struct Slot<T>
+{
+	path: String,
+	// None while the resource is being loaded or if loading has failed
+	value: Option<T>,
+	// Shared with every handle, so handles are counted without going through the storage
+	refs: Rc<()>
+}
+
+pub struct Storage<T>
+{
+	slots: Vec<Option<Slot<T>>>,
+	paths: HashMap<String, usize>
+}
+
+impl<T> Storage<T>
+{
+	fn new() -> Self
+	{
+		Self
+		{
+			slots: vec![],
+			paths: HashMap::new()
+		}
+	}
+
+	fn slot(&mut self, index: usize) -> &mut Slot<T> { self.slots[index].as_mut().unwrap() }
+
+	fn find(&self, path: &str) -> Option<usize> { self.paths.get(path).copied() }
+
+	fn findOrInsert(&mut self, path: &str) -> usize
+	{
+		if let Some(i) = self.find(path) { return i; }
+		let slot = Some(Slot { path: path.to_string(), value: None, refs: Rc::new(()) });
+		let index = match self.slots.iter().position(|s| s.is_none())
+		{
+			Some(i) => { self.slots[i] = slot; i }
+			None => { self.slots.push(slot); self.slots.len() - 1 }
+		};
+		self.paths.insert(path.to_string(), index);
+		index
+	}
+
+	fn get(&mut self, path: &str) -> Option<&mut T>
+	{
+		let i = self.find(path)?;
+		self.slot(i).value.as_mut()
+	}
+
+	// Unloads everything that has no references left, unless it's still being loaded
+	fn collect(&mut self, keep: impl Fn(&str) -> bool)
+	{
+		for s in &mut self.slots
+		{
+			if s.as_ref().is_some_and(|x| Rc::strong_count(&x.refs) == 1 && !keep(&x.path))
+			{
+				self.paths.remove(&s.take().unwrap().path);
+			}
+		}
+	}
+
+	// Unloads everything that has no references left, the rest is read again by reload()
+	fn clear(&mut self, reload: impl Fn(&str, &mut T))
+	{
+		for s in &mut self.slots
+		{
+			match s
+			{
+				Some(x) if Rc::strong_count(&x.refs) > 1 =>
+				{
+					if let Some(v) = &mut x.value { reload(&x.path, v); }
+				}
+				Some(_) => { self.paths.remove(&s.take().unwrap().path); }
+				None => {}
+			}
+		}
+	}
+
+	fn getPaths(&self) -> Vec<String>
+	{
+		self.slots.iter().flatten()
+			.filter(|s| s.value.is_some())
+			.map(|s| s.path.clone())
+			.collect()
+	}
+
+	fn getCount(&self) -> usize
+	{
+		self.slots.iter().flatten().filter(|s| s.value.is_some()).count()
+	}
+}
+
+impl<T: Resource> Storage<T>
+{
+	fn handle(&mut self, index: usize) -> Handle<T>
+	{
+		Handle { index, refs: self.slot(index).refs.clone(), _type: PhantomData }
+	}
+}
+
+// A counted reference to a resource, it's unloaded at the end of the frame
+// in which the last handle is dropped
+pub struct Handle<T: Resource>
+{
+	index: usize,
+	refs: Rc<()>,
+	_type: PhantomData<T>
+}
+
+impl<T: Resource> Handle<T>
+{
+
+	pub fn get(&self) -> Option<&'static mut T> { T::storage().slot(self.index).value.as_mut() }
+
+	pub fn getPath(&self) -> String { T::storage().slot(self.index).path.clone() }
+
+	pub fn isLoaded(&self) -> bool { self.get().is_some() }
+}
+
+impl Handle<Texture>
+{
+	pub fn getId(&self) -> u32 { self.get().map_or(0, |t| t.id) }
+
+	pub fn getSize(&self) -> glam::Vec2 { self.get().map_or(glam::Vec2::ZERO, |t| t.size) }
+}
+
+impl<T: Resource> Clone for Handle<T>
+{
+	fn clone(&self) -> Self { Self { index: self.index, refs: self.refs.clone(), _type: PhantomData } }
+}
+
+pub struct Resources
+{
+	textures: Storage<Texture>,
+	shaders: Storage<Shader>,
+	fonts: Storage<Font>,
+	// Shaders are requested by name on every draw, so the cache keeps them loaded
+	shaderCache: HashMap<String, Handle<Shader>>,
+	// Textures preloaded from scripts, kept until they are released
+	held: HashMap<String, Handle<Texture>>,
+	worker: Option<(Sender<String>, Receiver<Decoded>)>,
+	pending: HashSet<String>,
+	queued: usize,
+	loaded: usize
+}
+
+impl Resources
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			textures: Storage::new(),
+			shaders: Storage::new(),
+			fonts: Storage::new(),
+			shaderCache: HashMap::new(),
+			held: HashMap::new(),
+			worker: None,
+			pending: HashSet::new(),
+			queued: 0,
+			loaded: 0
+		}
+	}
+
+	// Loads the texture right away if it isn't in memory yet, preload() avoids waiting for it
+	pub fn texture(&mut self, path: String) -> Handle<Texture>
+	{
+		let index = self.textures.findOrInsert(&path);
+		let slot = self.textures.slot(index);
+		if slot.value.is_none()
+		{
+			match decode(&path)
+			{
+				Ok(data) => slot.value = Some(Texture::new(&data)),
+				Err(e) => println!("Failed to load texture from {path}: {e}")
+			}
+		}
+		self.textures.handle(index)
+	}
+
+	// A texture that failed to load is requested again
+	fn request(&mut self, path: String)
+	{
+		if self.pending.contains(&path) { return; }
+		if self.pending.is_empty()
+		{
+			self.queued = 0;
+			self.loaded = 0;
+		}
+		self.queued += 1;
+		self.pending.insert(path.clone());
+		self.startWorker();
+		if let Some((jobs, _)) = &self.worker { let _ = jobs.send(path); }
+	}
+
+	fn startWorker(&mut self)
+	{
+		if self.worker.is_some() { return; }
+		self.worker = Some(
+		{
+			let (jobs, queue) = mpsc::channel::<String>();
+			let (results, done) = mpsc::channel();
+			std::thread::spawn(move ||
+			{
+				for path in queue
+				{
+					let data = decode(&path);
+					if results.send((path, data)).is_err() { break; }
+				}
+			});
+			(jobs, done)
+		});
+	}
+
+	// The texture is decoded by the worker and uploaded by update(), the handle is kept until it's released
+	pub fn preload(&mut self, path: String)
+	{
+		if self.held.contains_key(&path) { return; }
+		let index = self.textures.findOrInsert(&path);
+		if self.textures.slot(index).value.is_none() { self.request(path.clone()); }
+		let h = self.textures.handle(index);
+		self.held.insert(path, h);
+	}
+
+	pub fn release(&mut self, path: &str)
+	{
+		self.held.remove(path);
+	}
+
+	pub fn isLoaded(&mut self, path: &str) -> bool
+	{
+		self.textures.get(path).is_some()
+	}
+
+	pub fn isLoading(&self) -> bool { !self.pending.is_empty() }
+
+	// Part of the requested textures that are done, 1 when nothing is loading
+	pub fn getProgress(&self) -> (f32, usize, usize)
+	{
+		let p = if self.queued == 0 { 1.0 } else { self.loaded as f32 / self.queued as f32 };
+		(p, self.loaded, self.queued)
+	}
+
+	// Uploads textures decoded by the worker and unloads unused resources, called once per frame
+	pub fn update(&mut self)
+	{
+		if let Some((_, done)) = &self.worker
+		{
+			while let Ok((path, data)) = done.try_recv()
+			{
+				self.pending.remove(&path);
+				self.loaded += 1;
+				let index = match self.textures.find(&path)
+				{
+					Some(i) => i,
+					None => continue
+				};
+				let slot = self.textures.slot(index);
+				if slot.value.is_some() { continue; }
+				match data
+				{
+					Ok(data) => slot.value = Some(Texture::new(&data)),
+					Err(e) => println!("Failed to load texture from {path}: {e}")
+				}
+			}
+		}
+
+		self.textures.collect(|p| self.pending.contains(p));
+		self.shaders.collect(|_| false);
+		self.fonts.collect(|_| false);
+	}
+
+	pub fn shader(&mut self, name: String) -> &'static Shader
+	{
+		if let Some(h) = self.shaderCache.get(&name) { return h.get().unwrap(); }
+
+		let index = self.shaders.findOrInsert(&name);
+		let slot = self.shaders.slot(index);
+		if slot.value.is_none()
+		{
+			slot.value = Some(Shader::load(
+				&(String::from("res/shaders/") + &name + ".vert"),
+				&(String::from("res/shaders/") + &name + ".frag")
+			));
+		}
+		let h = self.shaders.handle(index);
+		let s = h.get().unwrap();
+		self.shaderCache.insert(name, h);
+		s
+	}
+
+	// Every text with the same font shares its glyphs and atlas
+	pub fn font(&mut self, path: String) -> Handle<Font>
+	{
+		let index = self.fonts.findOrInsert(&path);
+		let slot = self.fonts.slot(index);
+		if slot.value.is_none() { slot.value = Some(Font::load(path)); }
+		self.fonts.handle(index)
+	}
+
+	pub fn getShaders(&mut self) -> impl Iterator<Item = &mut Shader>
+	{
+		self.shaders.slots.iter_mut().flatten().filter_map(|s| s.value.as_mut())
+	}
+
+	// The new image is uploaded into the same texture, so every sprite using it is updated
+	pub fn reloadTexture(&mut self, path: &str) -> bool
+	{
+		let t = match self.textures.get(path)
+		{
+			Some(t) => t,
+			None => return false
+		};
+		match decode(path)
+		{
+			Ok(data) =>
+			{
+				t.upload(&data);
+				true
+			}
+			Err(e) =>
+			{
+				println!("Failed to reload texture from {path}: {e}");
+				false
+			}
+		}
+	}
+
+	pub fn reloadShader(&mut self, name: &str) -> bool
+	{
+		let s = match self.shaders.get(name)
+		{
+			Some(s) => s,
+			None => return false
+		};
+		let ok = s.reload(
+			&(String::from("res/shaders/") + name + ".vert"),
+			&(String::from("res/shaders/") + name + ".frag")
+		);
+		if ok { Window::getCamera().resetShader(); }
+		else { println!("Shader {name} is not reloaded, the previous version is kept"); }
+		ok
+	}
+
+	pub fn getTexturePaths(&self) -> Vec<String> { self.textures.getPaths() }
+
+	pub fn getTextureCount(&self) -> usize { self.textures.getCount() }
+
+	pub fn getShaderNames(&self) -> Vec<String> { self.shaders.getPaths() }
+
+	// Unloads every resource, the ones that are still used are read from disk again
+	pub fn clearCache(&mut self)
+	{
+		self.shaderCache.clear();
+		self.held.clear();
+
+		self.textures.clear(|path, t|
+		{
+			match decode(path)
+			{
+				Ok(data) => t.upload(&data),
+				Err(e) => println!("Failed to reload texture from {path}: {e}")
+			}
+		});
+		self.fonts.clear(|path, f| f.reload(path.to_string()));
+		self.shaders.clear(|name, s|
+		{
+			s.reload(
+				&(String::from("res/shaders/") + name + ".vert"),
+				&(String::from("res/shaders/") + name + ".frag")
+			);
+		});
+		Window::getCamera().resetShader();
+	}
+}
@@ -89,6 +89,17 @@ impl Shader
 		s
 	}
 
+	// Keeps the current program if the new sources fail to compile or link
+	pub fn reload(&mut self, vertex: &str, fragment: &str) -> bool
+	{
+		let mut s = Shader::load(vertex, fragment);
+		let mut status = 0;
+		unsafe { gl::GetProgramiv(s.program, gl::LINK_STATUS, &mut status); }
+		if status == 0 || s.vertex == 0 || s.fragment == 0 { return false; }
+		std::mem::swap(self, &mut s);
+		true
+	}
+
 	pub fn activate(&self)
 	{
 		unsafe
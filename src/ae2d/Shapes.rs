@@ -35,8 +35,9 @@ impl Drawable for Rectangle
 		let shader = Window::getCamera()
 			.activateShader(String::from("shape"));
 		shader.setVec2("size", self.size);
-		shader.setMat4("model", self.ts.getMatrix());
-		shader.setVec4("clr", self.color);
+		let cam = Window::getCamera();
+		shader.setMat4("model", cam.getParent() * self.ts.getMatrix());
+		shader.setVec4("clr", self.color * glam::vec4(1.0, 1.0, 1.0, cam.getOpacity()));
 		Window::getCamera().universalVAO();
 		unsafe { gl::DrawArrays(gl::QUADS, 0, 4); }
 	}
@@ -15,7 +15,7 @@ use std::collections::HashMap;
 
 use glam::Vec4Swizzles;
 
-use crate::ae2d::{Camera::Drawable, Transformable::Transformable2D, Window::Window};
+use crate::ae2d::{Camera::Drawable, Resources::{Handle, Texture}, Transformable::Transformable2D, Window::Window};
 
 pub type SpriteList = HashMap<String, (glam::Vec4, glam::Vec2, glam::Vec2)>;
 
@@ -206,14 +206,24 @@ pub enum Interpolation
 
 impl Interpolation
 {
+	// Skeleton animations keep the old CubicOut curve, which overshoots twice the change
 	pub fn apply(&self, t: f32) -> f32
+	{
+		match self
+		{
+			Interpolation::CubicOut => 1.0 - (t - 1.0).powi(3),
+			_ => self.ease(t)
+		}
+	}
+
+	pub fn ease(&self, t: f32) -> f32
 	{
 		match self
 		{
 			Interpolation::Const => 0.0,
 			Interpolation::Linear => t,
 			Interpolation::CubicIn => t.powi(3),
-			Interpolation::CubicOut => 1.0 - (t - 1.0).powi(3),
+			Interpolation::CubicOut => 1.0 + (t - 1.0).powi(3),
 			Interpolation::CubicInOut =>
 				if t < 0.5 { 4.0 * t.powi(3) }
 				else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 },
@@ -471,7 +481,9 @@ pub struct Skeleton
 	sprites: SpriteList,
 	anims: HashMap<String, Animation>,
 	currentAnim: String,
-	texture: u32,
+	texture: Option<Handle<Texture>>,
+	// Sprite rectangles are in pixels until the texture size is known
+	unscaled: bool,
 	vbo: u32,
 	vao: u32,
 	ts: Transformable2D,
@@ -505,7 +517,8 @@ impl Skeleton
 			sprites: HashMap::new(),
 			anims: HashMap::new(),
 			currentAnim: String::new(),
-			texture: 0,
+			texture: None,
+			unscaled: false,
 			vbo, vao,
 			ts: Transformable2D::new(),
 			activeAnim: true,
@@ -533,8 +546,6 @@ impl Skeleton
 		let mut texPath = String::new();
 		if raw.is_err() { return texPath; }
 
-		let mut w = 0;
-		let mut h = 0;
 		if let Ok(root) = json::parse(&raw.unwrap())
 		{
 			self.sprites.clear();
@@ -544,7 +555,7 @@ impl Skeleton
 				{
 					texPath = value.as_str().unwrap().to_string()
 						.replace("\\", "/");
-					(w, h) = self.loadTexture(texPath.clone());
+					self.loadTexture(texPath.clone());
 					println!("Loading texture from {}", value.as_str().unwrap());
 				}
 				if var == "sprites"
@@ -577,13 +588,7 @@ impl Skeleton
 				}
 			}
 		}
-		for (_, (r, _, _)) in &mut self.sprites
-		{
-			r.x /= w as f32;
-			r.y /= h as f32;
-			r.z /= w as f32;
-			r.w /= h as f32;
-		}
+		self.unscaled = true;
 		texPath
 	}
 
@@ -612,24 +617,30 @@ impl Skeleton
 
 	pub fn getSL(&mut self) -> &mut SpriteList { &mut self.sprites }
 
-	pub fn loadTexture(&mut self, path: String) -> (u32, u32)
+	pub fn loadTexture(&mut self, path: String)
 	{
-		self.texture = Window::getTexture(path);
-		let mut w = 0;
-		let mut h = 0;
-		unsafe
+		self.texture = Some(Window::getTexture(path));
+	}
+
+	// Turns sprite rectangles into texture coordinates, false while the texture is being loaded
+	fn scaleSprites(&mut self) -> bool
+	{
+		let size = match &self.texture
 		{
-			gl::BindTexture(gl::TEXTURE_2D, self.texture);
-			gl::GetTexLevelParameteriv(
-				gl::TEXTURE_2D, 0,
-				gl::TEXTURE_WIDTH, &mut w
-			);
-			gl::GetTexLevelParameteriv(
-				gl::TEXTURE_2D, 0,
-				gl::TEXTURE_HEIGHT, &mut h
-			);
+			Some(t) if !t.isLoaded() => return false,
+			Some(t) => t.getSize(),
+			None => return true
+		};
+		if !self.unscaled { return true; }
+		for (_, (r, _, _)) in &mut self.sprites
+		{
+			r.x /= size.x;
+			r.y /= size.y;
+			r.z /= size.x;
+			r.w /= size.y;
 		}
-		(w as u32, h as u32)
+		self.unscaled = false;
+		true
 	}
 
 	pub fn setAnimation(&mut self, anim: String)
@@ -661,6 +672,7 @@ impl Drawable for Skeleton
 {
 	fn draw(&mut self)
 	{
+		if !self.scaleSprites() { return; }
 		if let Some(a) = self.anims.get_mut(&self.currentAnim)
 		{
 			a.update(&mut self.root, self.activeAnim);
@@ -679,7 +691,7 @@ impl Drawable for Skeleton
 
 		unsafe
 		{
-			gl::BindTexture(gl::TEXTURE_2D, self.texture);
+			gl::BindTexture(gl::TEXTURE_2D, self.texture.as_ref().map_or(0, Handle::getId));
 			Window::getCamera().bindVAO(self.vao);
 			gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
 			gl::BufferData(gl::ARRAY_BUFFER,
@@ -4,7 +4,7 @@ use glam::Vec4Swizzles;
 
 use crate::ae2d::Camera::Drawable;
 
-use super::{Transformable::Transformable2D, Window::Window};
+use super::{Resources::{Handle, Texture}, Shader::Shader, Transformable::Transformable2D, Window::Window};
 
 #[derive(Clone, Debug)]
 pub struct Animation
@@ -89,18 +89,31 @@ impl Animation
 	pub fn getCurrentFrame(&self) -> u8 { self.frames[self.currentFrame].0 }
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum SliceMode
+{
+	Stretch,
+	Tile
+}
+
 #[derive(Clone)]
 pub struct Sprite
 {
 	animations: HashMap<String, Animation>,
 	currentAnimation: String,
 	frames: Vec<glam::Vec4>,
-	texture: u32,
+	texture: Option<Handle<Texture>>,
 	rect: glam::Vec4,
 	texSize: glam::Vec2,
 	ts: Transformable2D,
 	color: glam::Vec4,
-	frameSize: glam::Vec2
+	frameSize: glam::Vec2,
+	// Frame size of an animated sprite, frames are cut once the texture is uploaded
+	cell: Option<glam::IVec2>,
+	// Nine-slice insets [left, top, right, bottom] in texture pixels, zero disables it
+	slice: glam::Vec4,
+	sliceMode: SliceMode,
+	size: Option<glam::Vec2>
 }
 
 impl Sprite
@@ -112,12 +125,16 @@ impl Sprite
 			animations: HashMap::new(),
 			currentAnimation: String::new(),
 			frames: vec![],
-			texture: 0,
+			texture: None,
 			rect: glam::Vec4::ZERO,
 			texSize: glam::Vec2::ZERO,
 			ts: Transformable2D::new(),
 			color: glam::Vec4::ONE,
-			frameSize: glam::Vec2::ZERO
+			frameSize: glam::Vec2::ZERO,
+			cell: None,
+			slice: glam::Vec4::ZERO,
+			sliceMode: SliceMode::Stretch,
+			size: None
 		}
 	}
 
@@ -132,26 +149,12 @@ impl Sprite
 		let src = src.unwrap();
 
 		let mut frame = glam::ivec2(0, 0);
-		let mut w = 0;
-		let mut h = 0;
 
 		for (section, value) in src.entries()
 		{
 			if section == "texture"
 			{
-				spr.texture = Window::getTexture(value.as_str().unwrap().to_string());
-				unsafe
-				{
-					gl::BindTexture(gl::TEXTURE_2D, spr.texture);
-					gl::GetTexLevelParameteriv(
-						gl::TEXTURE_2D, 0,
-						gl::TEXTURE_WIDTH, &mut w
-					);
-					gl::GetTexLevelParameteriv(
-						gl::TEXTURE_2D, 0,
-						gl::TEXTURE_HEIGHT, &mut h
-					);
-				}
+				spr.texture = Some(Window::getTexture(value.as_str().unwrap().to_string()));
 			}
 			if section == "size"
 			{
@@ -161,6 +164,7 @@ impl Sprite
 					s.nth(0).unwrap().as_i32().unwrap()
 				);
 			}
+			spr.parseSlice(section, value);
 			if section == "anims"
 			{
 				for (name, data) in value.entries()
@@ -173,37 +177,56 @@ impl Sprite
 			}
 		}
 
-		spr.texSize = glam::vec2(w as f32, h as f32);
-		
-		spr.calculateFrames((w, h), frame);
-
+		spr.cell = Some(frame);
+		spr.frameSize = glam::vec2(frame.x as f32, frame.y as f32);
+		spr.sync();
 		spr
 	}
 
+	// Either a PNG or a JSON with "texture" and nine-slice settings
 	pub fn image(path: String) -> Self
 	{
-		let mut spr = Sprite::default();
-		spr.texture = Window::getTexture(path);
-		let mut w = 0;
-		let mut h = 0;
-		unsafe
+		if path.ends_with(".json")
 		{
-			gl::BindTexture(gl::TEXTURE_2D, spr.texture);
-			gl::GetTexLevelParameteriv(
-				gl::TEXTURE_2D, 0,
-				gl::TEXTURE_WIDTH, &mut w
-			);
-			gl::GetTexLevelParameteriv(
-				gl::TEXTURE_2D, 0,
-				gl::TEXTURE_HEIGHT, &mut h
-			);
+			let src = json::parse(&std::fs::read_to_string(&path).unwrap_or_default());
+			let src = match src
+			{
+				Ok(x) => x,
+				Err(x) => { println!("Failed to load image {path}: {x}"); return Sprite::default(); }
+			};
+			let mut spr = Sprite::image(src["texture"].as_str().unwrap_or_default().to_string());
+			for (section, value) in src.entries() { spr.parseSlice(section, value); }
+			return spr;
 		}
-		spr.texSize = glam::vec2(w as f32, h as f32);
-		spr.frameSize = spr.texSize;
-		spr.rect = glam::vec4(0.0, 0.0, spr.frameSize.x, spr.frameSize.y);
+
+		let mut spr = Sprite::default();
+		spr.texture = Some(Window::getTexture(path));
+		spr.sync();
 		spr
 	}
 
+	// "slice": 8 or [left, top, right, bottom], "sliceMode": "stretch" or "tile"
+	fn parseSlice(&mut self, section: &str, value: &json::JsonValue)
+	{
+		if section == "slice"
+		{
+			self.slice = match value.as_f32()
+			{
+				Some(x) => glam::Vec4::splat(x),
+				None => glam::vec4(
+					value[0].as_f32().unwrap_or(0.0),
+					value[1].as_f32().unwrap_or(0.0),
+					value[2].as_f32().unwrap_or(0.0),
+					value[3].as_f32().unwrap_or(0.0)
+				)
+			};
+		}
+		if section == "sliceMode"
+		{
+			self.sliceMode = SliceMode::from(value.as_str().unwrap_or_default());
+		}
+	}
+
 	pub fn update(&mut self)
 	{
 		if self.animations.len() == 0 { return; }
@@ -213,6 +236,24 @@ impl Sprite
 		}
 	}
 
+	// The texture size is known only after it's uploaded, until then the sprite isn't drawn
+	fn sync(&mut self)
+	{
+		if self.texSize != glam::Vec2::ZERO { return; }
+		let size = match &self.texture
+		{
+			Some(t) if t.isLoaded() => t.getSize(),
+			_ => return
+		};
+		self.texSize = size;
+		match self.cell
+		{
+			Some(frame) => self.calculateFrames((size.x as i32, size.y as i32), frame),
+			None if self.rect == glam::Vec4::ZERO => self.setTextureRect(glam::vec4(0.0, 0.0, size.x, size.y)),
+			None => {}
+		}
+	}
+
 	fn calculateFrames(&mut self, size: (i32, i32), frame: glam::IVec2)
 	{
 		self.frames.clear();
@@ -273,8 +314,9 @@ impl Sprite
 		&mut self.ts
 	}
 
-	pub fn getFrameSize(&self) -> glam::Vec2
+	pub fn getFrameSize(&mut self) -> glam::Vec2
 	{
+		self.sync();
 		self.frameSize
 	}
 
@@ -288,9 +330,31 @@ impl Sprite
 		);
 	}
 
+	pub fn setSlice(&mut self, insets: glam::Vec4) { self.slice = insets; }
+	pub fn getSlice(&self) -> glam::Vec4 { self.slice }
+
+	pub fn setSliceMode(&mut self, mode: SliceMode) { self.sliceMode = mode; }
+
+	pub fn isSliced(&self) -> bool { self.slice != glam::Vec4::ZERO }
+
+	// Size of a nine-slice sprite before scaling, the frame size is used by default
+	pub fn setSize(&mut self, size: glam::Vec2) { self.size = Some(size); }
+	pub fn getSize(&mut self) -> glam::Vec2
+	{
+		self.sync();
+		match self.size
+		{
+			Some(s) if self.isSliced() => s,
+			_ => self.frameSize
+		}
+	}
+
+	pub fn setColorVec(&mut self, clr: glam::Vec4) { self.color = clr; }
+	pub fn getColor(&self) -> glam::Vec4 { self.color }
+
 	pub fn getBounds(&mut self) -> glam::Vec4
 	{
-		let s = self.getFrameSize();
+		let s = self.getSize();
 		let m = self.ts.getMatrix();
 		let p1 = m * glam::vec4(0.0, 0.0, 0.0, 1.0);
 		let p2 = m * glam::vec4(s.x, 0.0, 0.0, 1.0);
@@ -302,16 +366,22 @@ impl Sprite
 		glam::vec4(min.x, min.y, max.x - min.x, max.y - min.y)
 	}
 
-	pub fn getTexture(&self) -> u32 { self.texture }
+	pub fn getTexture(&self) -> u32 { self.texture.as_ref().map_or(0, Handle::getId) }
 
-	pub fn getTexSize(&self) -> glam::Vec2 { self.texSize }
+	pub fn getTexSize(&mut self) -> glam::Vec2
+	{
+		self.sync();
+		self.texSize
+	}
 }
 
 impl Drawable for Sprite
 {
 	fn draw(&mut self)
 	{
-		if !Window::getCamera().isVisible(self.getBounds()) { return; }
+		if self.texture.as_ref().is_some_and(|t| !t.isLoaded()) { return; }
+		let cam = Window::getCamera();
+		if !cam.isVisible(cam.transformRect(self.getBounds())) { return; }
 		self.update();
 		let s = Window::getCamera().
 			activateShader(String::from("sprite"));
@@ -320,14 +390,82 @@ impl Drawable for Sprite
 			else { self.getCurrentFrame() }
 		);
 		s.setVec2("texSize", self.texSize);
-		s.setMat4("model", self.ts.getMatrix());
-		s.setVec4("color", self.color);
+		s.setMat4("model", cam.getParent() * self.ts.getMatrix());
+		s.setVec4("color", self.color * glam::vec4(1.0, 1.0, 1.0, cam.getOpacity()));
 		unsafe
 		{
 			// gl::ActiveTexture(gl::TEXTURE0);
-			gl::BindTexture(gl::TEXTURE_2D, self.texture);
+			gl::BindTexture(gl::TEXTURE_2D, self.getTexture());
 			Window::getCamera().universalVAO();
-			gl::DrawArrays(gl::QUADS, 0, 4);
+			if self.isSliced() { self.drawSliced(s); }
+			else { gl::DrawArrays(gl::QUADS, 0, 4); }
+		}
+	}
+}
+
+impl Sprite
+{
+	// Every part of the frame is drawn as a separate quad, corners keep their size
+	// unless the sprite is too small for them, edges and center are stretched or tiled
+	fn drawSliced(&mut self, s: &Shader)
+	{
+		let frame = if self.animations.is_empty() { self.rect } else { self.getCurrentFrame() };
+		let size = self.getSize();
+		let i = self.slice;
+		let k = (size.x / (i.x + i.z)).min(size.y / (i.y + i.w)).min(1.0);
+		let d = i * k;
+
+		let sx = [0.0, i.x, frame.z - i.z, frame.z];
+		let sy = [0.0, i.y, frame.w - i.w, frame.w];
+		let dx = [0.0, d.x, size.x - d.z, size.x];
+		let dy = [0.0, d.y, size.y - d.w, size.y];
+		let model = Window::getCamera().getParent() * self.ts.getMatrix();
+		let tile = self.sliceMode == SliceMode::Tile;
+
+		for row in 0..3
+		{
+			for col in 0..3
+			{
+				let src = glam::vec2(sx[col + 1] - sx[col], sy[row + 1] - sy[row]);
+				let dst = glam::vec2(dx[col + 1] - dx[col], dy[row + 1] - dy[row]);
+				if src.x <= 0.0 || src.y <= 0.0 || dst.x <= 0.0 || dst.y <= 0.0 { continue; }
+
+				// A tiled part is split into pieces of the source size, the last one is cut
+				let step = glam::vec2(
+					if tile && col == 1 { src.x } else { dst.x },
+					if tile && row == 1 { src.y } else { dst.y }
+				);
+				let mut y = 0.0;
+				while y < dst.y
+				{
+					let mut x = 0.0;
+					while x < dst.x
+					{
+						let piece = glam::vec2(step.x.min(dst.x - x), step.y.min(dst.y - y));
+						let part = src * piece / step;
+						s.setVec4("frame", glam::vec4(frame.x + sx[col], frame.y + sy[row], part.x, part.y));
+						s.setMat4("model", model *
+							glam::Mat4::from_translation(glam::vec3(dx[col] + x, dy[row] + y, 0.0)) *
+							glam::Mat4::from_scale(glam::vec3(piece.x / part.x, piece.y / part.y, 1.0))
+						);
+						unsafe { gl::DrawArrays(gl::QUADS, 0, 4); }
+						x += step.x;
+					}
+					y += step.y;
+				}
+			}
+		}
+	}
+}
+
+impl From<&str> for SliceMode
+{
+	fn from(s: &str) -> Self
+	{
+		match s
+		{
+			"tile" => SliceMode::Tile,
+			_ => SliceMode::Stretch
 		}
 	}
 }
\ No newline at end of file
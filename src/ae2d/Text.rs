@@ -1,21 +1,33 @@
 use std::collections::HashMap;
 
-use super::{Camera::Drawable, Transformable::Transformable2D, Window::Window};
+use super::{Camera::Drawable, Resources::{Handle, Texture}, Transformable::Transformable2D, Window::Window};
 
 #[derive(Clone)]
 struct Glyph
 {
 	rect: glam::Vec4,
 	offset: glam::Vec2,
-	advance: u8
+	advance: f32
 }
 
-struct Font
+const TTF_SIZE: f32 = 48.0;
+
+pub struct Font
 {
 	texture: u32,
-	glyphs: HashMap<u16, Glyph>,
+	// Keeps the bitmap of a JSON font loaded, TTF fonts own their texture
+	atlas: Option<Handle<Texture>>,
+	glyphs: HashMap<char, Glyph>,
+	kerning: HashMap<(char, char), f32>,
 	height: f32,
-	bitmapSize: glam::Vec2
+	bitmapSize: glam::Vec2,
+	ttf: Option<fontdue::Font>,
+	ascent: f32,
+	pixels: Vec<u8>,
+	pen: glam::Vec2,
+	rowHeight: f32,
+	// Increased when the atlas grows, so texts sharing the font rebuild their coordinates
+	version: u32
 }
 
 impl Font
@@ -25,100 +37,491 @@ impl Font
 		Self
 		{
 			texture: 0,
+			atlas: None,
 			glyphs: HashMap::new(),
+			kerning: HashMap::new(),
 			height: 0.0,
-			bitmapSize: glam::Vec2::ZERO
+			bitmapSize: glam::Vec2::ZERO,
+			ttf: None,
+			ascent: 0.0,
+			pixels: vec![],
+			pen: glam::Vec2::ZERO,
+			rowHeight: 0.0,
+			version: 0
 		}
 	}
 
 	pub fn load(path: String) -> Self
+	{
+		let ext = path.rsplit('.').next().unwrap_or_default().to_lowercase();
+		match ext.as_str()
+		{
+			"ttf" | "otf" => Self::loadTTF(path),
+			_ => Self::loadJSON(path)
+		}
+	}
+
+	// Texts using the font are laid out again, since the atlas may change
+	pub fn reload(&mut self, path: String)
+	{
+		let version = self.version + 1;
+		*self = Font::load(path);
+		self.version = version;
+	}
+
+	fn loadJSON(path: String) -> Self
 	{
 		let mut font = Self::default();
 
 		let f = json::parse(
-			&std::fs::read_to_string(path).unwrap()
+			&std::fs::read_to_string(&path).unwrap_or_default()
 		);
-		if f.is_err() { return font; }
+		if f.is_err() { println!("Failed to load font {path}"); return font; }
 		let f = f.unwrap();
 
-		let mut w = 0;
-		let mut h = 0;
-
 		for section in f.entries()
 		{
 			if section.0 == "lineHeight" { font.height = section.1.as_f32().unwrap(); }
 			if section.0 == "texture"
 			{
-				font.texture = Window::getTexture(
+				font.atlas = Some(Window::getTexture(
 					section.1.as_str().unwrap().to_string()
-				);
-				unsafe
-				{
-					gl::BindTexture(gl::TEXTURE_2D, font.texture);
-					gl::GetTexLevelParameteriv(
-						gl::TEXTURE_2D, 0,
-						gl::TEXTURE_WIDTH, &mut w
-					);
-					gl::GetTexLevelParameteriv(
-						gl::TEXTURE_2D, 0,
-						gl::TEXTURE_HEIGHT, &mut h
-					);
-				}
+				));
 			}
 			if section.0 == "glyphs"
 			{
 				for glyph in section.1.members()
 				{
-					let mut id = 0;
+					let mut id = None;
 					let mut g = Glyph
 					{
 						rect: glam::Vec4::ZERO,
 						offset: glam::Vec2::ZERO,
-						advance: 0
+						advance: 0.0
 					};
 					for x in glyph.entries()
 					{
-						if x.0 == "id" { id = x.1.as_u16().unwrap(); }
+						if x.0 == "id" { id = x.1.as_u32().and_then(char::from_u32); }
 						if x.0 == "x" { g.rect.x = x.1.as_f32().unwrap(); }
 						if x.0 == "y" { g.rect.y = x.1.as_f32().unwrap(); }
 						if x.0 == "w" { g.rect.z = x.1.as_f32().unwrap(); }
 						if x.0 == "h" { g.rect.w = x.1.as_f32().unwrap(); }
 						if x.0 == "ox" { g.offset.x = x.1.as_f32().unwrap(); }
 						if x.0 == "oy" { g.offset.y = x.1.as_f32().unwrap(); }
-						if x.0 == "advance" { g.advance = x.1.as_u8().unwrap(); }
+						if x.0 == "advance" { g.advance = x.1.as_f32().unwrap(); }
+					}
+					match id
+					{
+						Some(id) => { font.glyphs.insert(id, g); }
+						None => println!("Skipping glyph with a wrong id in {path}: {}", glyph["id"])
+					}
+				}
+			}
+			if section.0 == "kerning"
+			{
+				for pair in section.1.members()
+				{
+					let a = pair["first"].as_u32().and_then(char::from_u32);
+					let b = pair["second"].as_u32().and_then(char::from_u32);
+					if let (Some(a), Some(b)) = (a, b)
+					{
+						font.kerning.insert((a, b), pair["amount"].as_f32().unwrap_or(0.0));
 					}
-					font.glyphs.insert(id, g);
 				}
 			}
 		}
 
-		font.bitmapSize = glam::vec2(w as f32, h as f32);
 		font
 	}
 
-	pub fn getGlyph(&self, c: char) -> Option<&Glyph>
+	fn loadTTF(path: String) -> Self
+	{
+		let mut font = Self::default();
+
+		let data = match std::fs::read(&path)
+		{
+			Ok(x) => x,
+			Err(x) => { println!("Failed to read font {path}: {x}"); return font; }
+		};
+		let ttf = match fontdue::Font::from_bytes(data, fontdue::FontSettings
+		{
+			scale: TTF_SIZE,
+			..Default::default()
+		})
+		{
+			Ok(x) => x,
+			Err(x) => { println!("Failed to parse font {path}: {x}"); return font; }
+		};
+
+		let (ascent, descent) = match ttf.horizontal_line_metrics(TTF_SIZE)
+		{
+			Some(lm) => (lm.ascent, lm.descent),
+			None =>
+			{
+				println!("Font {path} has no horizontal metrics, the line height is guessed");
+				(TTF_SIZE * 0.8, -TTF_SIZE * 0.2)
+			}
+		};
+		font.height = (ascent - descent).ceil();
+		font.ascent = ascent;
+		font.ttf = Some(ttf);
+
+		font.bitmapSize = glam::vec2(512.0, 512.0);
+		font.pixels = vec![0; 512 * 512 * 4];
+		unsafe
+		{
+			gl::GenTextures(1, &mut font.texture);
+			gl::BindTexture(gl::TEXTURE_2D, font.texture);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+			gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+		}
+		font.uploadAtlas();
+
+		font
+	}
+
+	fn uploadAtlas(&self)
+	{
+		unsafe
+		{
+			gl::BindTexture(gl::TEXTURE_2D, self.texture);
+			gl::TexImage2D(
+				gl::TEXTURE_2D,
+				0,
+				gl::RGBA as i32,
+				self.bitmapSize.x as i32,
+				self.bitmapSize.y as i32,
+				0,
+				gl::RGBA,
+				gl::UNSIGNED_BYTE,
+				self.pixels.as_ptr() as *const _
+			);
+		}
+	}
+
+	// Glyphs of a JSON font can't be placed until its bitmap is uploaded
+	fn isReady(&mut self) -> bool
+	{
+		let a = match &self.atlas
+		{
+			Some(a) => a,
+			None => return true
+		};
+		if !a.isLoaded() { return false; }
+		self.texture = a.getId();
+		self.bitmapSize = a.getSize();
+		true
+	}
+
+	// Rasterizes missing glyphs of a TTF font into the atlas.
+	// Must be called before building vertices, since the atlas may grow.
+	pub fn prepare(&mut self, text: &str)
+	{
+		if self.ttf.is_none() { return; }
+		for c in text.chars()
+		{
+			if c == '\n' || self.glyphs.contains_key(&c) { continue; }
+			self.rasterize(c);
+		}
+	}
+
+	fn rasterize(&mut self, c: char)
+	{
+		let ttf = self.ttf.as_ref().unwrap();
+		if ttf.lookup_glyph_index(c) == 0 && !c.is_whitespace() { return; }
+		let (m, bitmap) = ttf.rasterize(c, TTF_SIZE);
+
+		let w = m.width as f32;
+		let h = m.height as f32;
+		if self.pen.x + w + 1.0 > self.bitmapSize.x
+		{
+			self.pen = glam::vec2(0.0, self.pen.y + self.rowHeight + 1.0);
+			self.rowHeight = 0.0;
+		}
+		while self.pen.y + h + 1.0 > self.bitmapSize.y
+		{
+			self.bitmapSize.y *= 2.0;
+			self.pixels.resize((self.bitmapSize.x * self.bitmapSize.y * 4.0) as usize, 0);
+			self.uploadAtlas();
+			self.version += 1;
+		}
+
+		let stride = self.bitmapSize.x as usize;
+		let (px, py) = (self.pen.x as usize, self.pen.y as usize);
+		let mut region = Vec::with_capacity(m.width * m.height * 4);
+		for y in 0..m.height
+		{
+			for x in 0..m.width
+			{
+				let a = bitmap[y * m.width + x];
+				region.extend_from_slice(&[255, 255, 255, a]);
+				let i = ((py + y) * stride + px + x) * 4;
+				self.pixels[i..i + 4].copy_from_slice(&[255, 255, 255, a]);
+			}
+		}
+		if !region.is_empty()
+		{
+			unsafe
+			{
+				gl::BindTexture(gl::TEXTURE_2D, self.texture);
+				gl::TexSubImage2D(
+					gl::TEXTURE_2D, 0,
+					px as i32, py as i32,
+					m.width as i32, m.height as i32,
+					gl::RGBA, gl::UNSIGNED_BYTE,
+					region.as_ptr() as *const _
+				);
+			}
+		}
+
+		self.glyphs.insert(c, Glyph
+		{
+			rect: glam::vec4(self.pen.x, self.pen.y, w, h),
+			offset: glam::vec2(m.xmin as f32, self.ascent - m.ymin as f32 - h),
+			advance: m.advance_width
+		});
+
+		self.pen.x += w + 1.0;
+		self.rowHeight = self.rowHeight.max(h);
+	}
+
+	// Advance correction between two characters, in font units
+	pub fn kern(&self, a: char, b: char) -> f32
+	{
+		match &self.ttf
+		{
+			Some(ttf) => ttf.horizontal_kern(a, b, TTF_SIZE).unwrap_or(0.0),
+			None => self.kerning.get(&(a, b)).copied().unwrap_or(0.0)
+		}
+	}
+
+	fn getGlyph(&self, c: char) -> Option<&Glyph>
 	{
-		let g = self.glyphs.get(&(c as u16));
+		let g = self.glyphs.get(&c);
 		match g
 		{
-			None => { println!("Символ не найден: {c}({})", c as u16); g },
+			None => { println!("Символ не найден: {c}({})", c as u32); g },
 			Some(_) => g
 		}
 	}
 }
 
+impl Drop for Font
+{
+	fn drop(&mut self)
+	{
+		if self.ttf.is_some()
+		{
+			unsafe { gl::DeleteTextures(1, &self.texture); }
+		}
+	}
+}
+
+pub struct IconSet
+{
+	atlas: Option<Handle<Texture>>,
+	icons: HashMap<String, glam::Vec4>
+}
+
+impl IconSet
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			atlas: None,
+			icons: HashMap::new()
+		}
+	}
+
+	pub fn load(path: &str) -> Self
+	{
+		let mut set = Self::new();
+
+		let src = std::fs::read_to_string(path);
+		if src.is_err() { return set; }
+		let src = match json::parse(&src.unwrap())
+		{
+			Ok(x) => x,
+			Err(x) => { println!("Failed to load icons from {path}: {x}"); return set; }
+		};
+
+		for (name, value) in src.entries()
+		{
+			if name == "texture"
+			{
+				set.atlas = Some(Window::getTexture(value.as_str().unwrap().to_string()));
+			}
+			if name == "icons"
+			{
+				for (icon, rect) in value.entries()
+				{
+					set.icons.insert(icon.to_string(), glam::vec4(
+						rect[0].as_f32().unwrap_or(0.0),
+						rect[1].as_f32().unwrap_or(0.0),
+						rect[2].as_f32().unwrap_or(0.0),
+						rect[3].as_f32().unwrap_or(0.0)
+					));
+				}
+			}
+		}
+
+		set
+	}
+
+	fn isReady(&self) -> bool { self.atlas.as_ref().is_none_or(Handle::isLoaded) }
+}
+
+enum Token
+{
+	Char(char, glam::Vec4, bool),
+	Icon(String),
+	Newline
+}
+
+// #rgb, #rgba, #rrggbb or #rrggbbaa
+pub fn parseColor(s: &str) -> Option<glam::Vec4>
+{
+	let hex = s.strip_prefix('#')?;
+	let digits: Vec<u8> = hex.chars()
+		.map(|c| c.to_digit(16).map(|x| x as u8))
+		.collect::<Option<_>>()?;
+	let c: Vec<u8> = match digits.len()
+	{
+		3 | 4 => digits.iter().map(|x| x * 17).collect(),
+		6 | 8 => digits.chunks(2).map(|x| x[0] * 16 + x[1]).collect(),
+		_ => return None
+	};
+	Some(glam::vec4(
+		c[0] as f32 / 255.0,
+		c[1] as f32 / 255.0,
+		c[2] as f32 / 255.0,
+		c.get(3).map(|x| *x as f32 / 255.0).unwrap_or(1.0)
+	))
+}
+
+// [color=#ff0]...[/color], [b]...[/b], [icon=name], "[[" is a literal bracket
+fn parseMarkup(text: &str, markup: bool) -> Vec<Token>
+{
+	let mut tokens = vec![];
+	let mut colors = vec![glam::Vec4::ONE];
+	let mut bold = 0;
+	let mut rest = text;
+
+	while let Some(c) = rest.chars().next()
+	{
+		rest = &rest[c.len_utf8()..];
+		if c == '\n' { tokens.push(Token::Newline); continue; }
+		if c != '[' || !markup
+		{
+			tokens.push(Token::Char(c, *colors.last().unwrap(), bold > 0));
+			continue;
+		}
+		if let Some(r) = rest.strip_prefix('[')
+		{
+			tokens.push(Token::Char('[', *colors.last().unwrap(), bold > 0));
+			rest = r;
+			continue;
+		}
+
+		let tag = rest.split_once(']').map(|x| x.0).unwrap_or_default();
+		let known = match tag.split_once('=')
+		{
+			Some(("color", v)) => parseColor(v).map(|x| colors.push(x)).is_some(),
+			Some(("icon", v)) => { tokens.push(Token::Icon(v.to_string())); true }
+			_ => match tag
+			{
+				"/color" => { if colors.len() > 1 { colors.pop(); } true }
+				"b" => { bold += 1; true }
+				"/b" => { bold -= (bold > 0) as i32; true }
+				_ => false
+			}
+		};
+
+		if known { rest = &rest[tag.len() + 1..]; }
+		else { tokens.push(Token::Char('[', *colors.last().unwrap(), bold > 0)); }
+	}
+
+	tokens
+}
+
+// Glyph or icon placed on a line, advance is in font units
+struct Item
+{
+	token: usize,
+	advance: f32,
+	space: bool
+}
+
+struct Line
+{
+	items: Vec<Item>,
+	hard: bool
+}
+
+impl Line
+{
+	fn new() -> Self { Self { items: vec![], hard: false } }
+
+	// Number of items without trailing spaces
+	fn visible(&self) -> usize
+	{
+		self.items.iter().rposition(|x| !x.space).map(|x| x + 1).unwrap_or(0)
+	}
+
+	fn width(&self) -> f32
+	{
+		self.items[..self.visible()].iter().map(|x| x.advance).sum()
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Align
+{
+	Left,
+	Center,
+	Right,
+	Justify
+}
+
+impl From<&str> for Align
+{
+	fn from(value: &str) -> Self
+	{
+		match value
+		{
+			"center" => Align::Center,
+			"right" => Align::Right,
+			"justify" => Align::Justify,
+			_ => Align::Left
+		}
+	}
+}
+
 pub struct Text
 {
-	font: Font,
+	font: Option<Handle<Font>>,
+	version: u32,
 	vbo: u32,
 	vao: u32,
 	text: String,
+	source: String,
 	update: bool,
 	ts: Transformable2D,
 	vertices: i32,
 	size: f32,
 	dimensions: glam::Vec2,
-	color: glam::Vec4
+	color: glam::Vec4,
+	markup: bool,
+	localized: bool,
+	batches: Vec<(u32, i32, i32)>,
+	maxWidth: f32,
+	align: Align,
+	lineSpacing: f32,
+	maxLines: usize,
+	ellipsis: bool
 }
 
 impl Text
@@ -134,78 +537,164 @@ impl Text
 		}
 		Self
 		{
-			font: Font::default(),
+			font: None,
+			version: 0,
 			vao, vbo,
 			text: String::new(),
+			source: String::new(),
 			update: false,
 			ts: Transformable2D::new(),
 			vertices: 0,
 			size: 0.0,
 			dimensions: glam::Vec2::ZERO,
-			color: glam::Vec4::ONE
+			color: glam::Vec4::ONE,
+			markup: true,
+			localized: true,
+			batches: vec![],
+			maxWidth: 0.0,
+			align: Align::Left,
+			lineSpacing: 1.0,
+			maxLines: 0,
+			ellipsis: true
 		}
 	}
 
 	pub fn setFont(&mut self, path: String)
 	{
-		self.font = Font::load(path);
+		self.font = Some(Window::getResources().font(path));
 		self.update = true;
 	}
 
+	// "@key" strings are looked up in the current language table
 	pub fn setString(&mut self, txt: String)
 	{
-		self.text = txt;
+		self.text = if self.localized { Window::getLocale().resolve(&txt) } else { txt.clone() };
+		self.source = txt;
 		self.update = true;
 	}
 
+	pub fn relocalize(&mut self)
+	{
+		if self.localized && self.source.starts_with('@')
+		{
+			self.setString(self.source.clone());
+		}
+	}
+
+	fn getFont(&self) -> Option<&'static mut Font> { self.font.as_ref().and_then(Handle::get) }
+
+	// The font is shared, so the text is also rebuilt when its atlas grows
+	fn isOutdated(&self) -> bool
+	{
+		self.update || self.getFont().is_some_and(|f| f.version != self.version)
+	}
+
 	fn reload(&mut self)
 	{
+		let font = match self.getFont()
+		{
+			Some(f) => f,
+			None =>
+			{
+				self.update = false;
+				self.vertices = 0;
+				self.dimensions = glam::Vec2::ZERO;
+				return;
+			}
+		};
+		if !font.isReady() || !Window::getIcons().isReady() { return; }
 		self.update = false;
-		let b = self.font.bitmapSize;
 
-		let mut line = Vec::<f32>::new();
+		let mut tokens = parseMarkup(&self.text, self.markup);
+		let chars: String = tokens.iter()
+			.filter_map(|t| if let Token::Char(c, _, _) = t { Some(*c) } else { None })
+			.collect();
+		font.prepare(&chars);
+		if self.maxLines > 0 && self.ellipsis { font.prepare("…"); }
+
+		let b = font.bitmapSize;
+		let icons = Window::getIcons();
+		let iconTexture = icons.atlas.as_ref().map_or(0, Handle::getId);
+		let s = icons.atlas.as_ref().map_or(glam::Vec2::ZERO, Handle::getSize);
+		let bold = font.height * 0.04;
+		let lines = self.layout(font, &mut tokens);
+		let lineHeight = font.height * self.lineSpacing;
+		let widest = lines.iter().map(|l| l.width()).fold(0.0, f32::max);
+		let boxWidth = if self.maxWidth > 0.0 { self.maxWidth * font.height / self.size } else { widest };
 
-		let mut x = 0.0;
-		let mut y = 0.0;
+		let mut line = Vec::<f32>::new();
+		let mut iconQuads = HashMap::<u32, Vec<f32>>::new();
 
-		for c in self.text.chars()
+		for (n, l) in lines.iter().enumerate()
 		{
-			if c == '\n'
+			let free = boxWidth - l.width();
+			let spaces = l.items[..l.visible()].iter().filter(|i| i.space).count();
+			let (mut x, gap) = match self.align
 			{
-				y += self.font.height;
-				x = 0.0;
-				continue;
+				Align::Left => (0.0, 0.0),
+				Align::Center => (free / 2.0, 0.0),
+				Align::Right => (free, 0.0),
+				Align::Justify if l.hard || spaces == 0 => (0.0, 0.0),
+				Align::Justify => (0.0, free / spaces as f32)
+			};
+			let y = n as f32 * lineHeight;
+
+			for item in &l.items
+			{
+				match &tokens[item.token]
+				{
+					Token::Char(c, clr, thick) =>
+					{
+						let g = font.glyphs.get(c).unwrap();
+						let uv = glam::vec4(
+							g.rect.x / b.x, g.rect.y / b.y,
+							(g.rect.x + g.rect.z) / b.x, (g.rect.y + g.rect.w) / b.y
+						);
+						let pos = glam::vec2(x, y) + g.offset;
+						let size = glam::vec2(g.rect.z, g.rect.w);
+						Text::pushQuad(&mut line, pos, size, uv, *clr);
+						if *thick
+						{
+							Text::pushQuad(&mut line, pos + glam::vec2(bold, 0.0), size, uv, *clr);
+						}
+					}
+					Token::Icon(name) =>
+					{
+						let r = icons.icons[name];
+						Text::pushQuad(
+							iconQuads.entry(iconTexture).or_default(),
+							glam::vec2(x, y), glam::vec2(item.advance, font.height),
+							glam::vec4(r.x / s.x, r.y / s.y, (r.x + r.z) / s.x, (r.y + r.w) / s.y),
+							glam::Vec4::ONE
+						);
+					}
+					Token::Newline => {}
+				}
+				x += item.advance + if item.space { gap } else { 0.0 };
 			}
-			let g = self.font.getGlyph(c);
-			if g.is_none() { continue; }
-			let g = g.unwrap();
-			
-			line.append(&mut vec![
-				x + g.offset.x, y + g.offset.y,
-				g.rect.x / b.x, g.rect.y / b.y,
-
-				x + g.offset.x + g.rect.z, y + g.offset.y,
-				(g.rect.x + g.rect.z) / b.x, g.rect.y / b.y,
-
-				x + g.offset.x + g.rect.z, y + g.offset.y + g.rect.w,
-				(g.rect.x + g.rect.z) / b.x, (g.rect.y + g.rect.w) / b.y,
-
-				x + g.offset.x, y + g.offset.y + g.rect.w,
-				g.rect.x / b.x, (g.rect.y + g.rect.w) / b.y
-			]);
-			
-			x += g.advance as f32;
 		}
+
+		self.batches = vec![(font.texture, 0, line.len() as i32 / 8)];
+		for (tex, mut quads) in iconQuads
+		{
+			self.batches.push((tex, line.len() as i32 / 8, quads.len() as i32 / 8));
+			line.append(&mut quads);
+		}
+
+		self.vertices = line.len() as i32 / 8;
+		self.version = font.version;
 		
-		self.vertices = line.len() as i32 / 4;
-		
-		let scale = self.size / self.font.height;
+		let scale = self.size / font.height;
 
-		self.dimensions = glam::Vec2::ZERO;
+		self.dimensions = match self.vertices
+		{
+			0 => glam::Vec2::ZERO,
+			_ => glam::vec2(boxWidth, (lines.len() - 1) as f32 * lineHeight + font.height) * scale
+		};
 		
 		for i in 0..self.vertices as usize
 		{
-			let x = i * 4;
+			let x = i * 8;
 			line[x] *= scale;
 			line[x + 1] *= scale;
 
@@ -226,10 +715,170 @@ impl Text
 			gl::VertexAttribPointer(
 				0, 4, gl::FLOAT,
 				gl::FALSE,
-				(4 * size_of::<f32>()) as _,
+				(8 * size_of::<f32>()) as _,
 				0 as _
 			);
+			gl::EnableVertexAttribArray(1);
+			gl::VertexAttribPointer(
+				1, 4, gl::FLOAT,
+				gl::FALSE,
+				(8 * size_of::<f32>()) as _,
+				(4 * size_of::<f32>()) as _
+			);
+		}
+	}
+
+	fn advance(&self, font: &Font, t: &Token) -> Option<f32>
+	{
+		match t
+		{
+			Token::Char(c, _, thick) => font.getGlyph(*c).map(|g|
+				g.advance + if *thick { font.height * 0.04 } else { 0.0 }
+			),
+			Token::Icon(name) => match Window::getIcons().icons.get(name)
+			{
+				Some(r) if r.w > 0.0 => Some(font.height * r.z / r.w),
+				Some(_) => Some(font.height),
+				None => { println!("Icon not found: {name}"); None }
+			},
+			Token::Newline => None
+		}
+	}
+
+	// Splits tokens into lines, wrapping on spaces when the max width is set.
+	// May append ellipsis tokens to the list.
+	fn layout(&self, font: &Font, tokens: &mut Vec<Token>) -> Vec<Line>
+	{
+		let maxWidth = if self.maxWidth > 0.0 && self.size > 0.0
+			{ self.maxWidth * font.height / self.size } else { f32::INFINITY };
+
+		let mut lines = vec![Line::new()];
+		for (i, t) in tokens.iter().enumerate()
+		{
+			if let Token::Newline = t
+			{
+				lines.last_mut().unwrap().hard = true;
+				lines.push(Line::new());
+				continue;
+			}
+			let advance = match self.advance(font, t) { Some(x) => x, None => continue };
+			let space = matches!(t, Token::Char(c, _, _) if c.is_whitespace());
+			let item = Item { token: i, advance, space };
+
+			let l = lines.last_mut().unwrap();
+			if let (Some(Token::Char(prev, _, _)), Token::Char(c, _, _)) = (i.checked_sub(1).map(|x| &tokens[x]), t)
+			{
+				if let Some(last) = l.items.last_mut().filter(|x| x.token == i - 1)
+				{
+					last.advance += font.kern(*prev, *c);
+				}
+			}
+			if !space && !l.items.is_empty() && l.width() + advance > maxWidth
+			{
+				let rest = match l.items.iter().rposition(|x| x.space)
+				{
+					Some(p) => l.items.split_off(p + 1),
+					None => vec![]
+				};
+				lines.push(Line { items: rest, hard: false });
+			}
+			lines.last_mut().unwrap().items.push(item);
+		}
+		lines.last_mut().unwrap().hard = true;
+
+		if self.maxLines > 0 && lines.len() > self.maxLines
+		{
+			lines.truncate(self.maxLines);
+			if self.ellipsis
+			{
+				let l = lines.last_mut().unwrap();
+				l.hard = true;
+				let clr = l.items.iter().rev()
+					.find_map(|x| if let Token::Char(_, c, _) = tokens[x.token] { Some(c) } else { None })
+					.unwrap_or(glam::Vec4::ONE);
+
+				let dots: Vec<char> = match font.glyphs.get(&'…')
+				{
+					Some(_) => vec!['…'],
+					None => vec!['.'; 3]
+				};
+				let mut tail = vec![];
+				for c in dots
+				{
+					let t = Token::Char(c, clr, false);
+					if let Some(advance) = self.advance(font, &t)
+					{
+						tail.push(Item { token: tokens.len(), advance, space: false });
+						tokens.push(t);
+					}
+				}
+				let dotsWidth: f32 = tail.iter().map(|x| x.advance).sum();
+
+				while !l.items.is_empty() &&
+					(l.width() + dotsWidth > maxWidth || l.items.last().unwrap().space)
+				{
+					l.items.pop();
+				}
+				l.items.append(&mut tail);
+			}
 		}
+
+		lines
+	}
+
+	pub fn setMaxWidth(&mut self, width: f32)
+	{
+		if self.maxWidth == width.max(0.0) { return; }
+		self.maxWidth = width.max(0.0);
+		self.update = true;
+	}
+
+	pub fn setAlign(&mut self, align: Align)
+	{
+		self.align = align;
+		self.update = true;
+	}
+
+	pub fn setLineSpacing(&mut self, spacing: f32)
+	{
+		self.lineSpacing = spacing;
+		self.update = true;
+	}
+
+	pub fn setMaxLines(&mut self, lines: usize)
+	{
+		self.maxLines = lines;
+		self.update = true;
+	}
+
+	pub fn setEllipsis(&mut self, ellipsis: bool)
+	{
+		self.ellipsis = ellipsis;
+		self.update = true;
+	}
+
+	// Position, texture coordinates and color of each corner
+	fn pushQuad(v: &mut Vec<f32>, pos: glam::Vec2, size: glam::Vec2, uv: glam::Vec4, clr: glam::Vec4)
+	{
+		v.extend_from_slice(&[
+			pos.x, pos.y, uv.x, uv.y, clr.x, clr.y, clr.z, clr.w,
+			pos.x + size.x, pos.y, uv.z, uv.y, clr.x, clr.y, clr.z, clr.w,
+			pos.x + size.x, pos.y + size.y, uv.z, uv.w, clr.x, clr.y, clr.z, clr.w,
+			pos.x, pos.y + size.y, uv.x, uv.w, clr.x, clr.y, clr.z, clr.w
+		]);
+	}
+
+	pub fn setMarkup(&mut self, markup: bool)
+	{
+		self.markup = markup;
+		self.update = true;
+	}
+
+	// Whether "@key" strings are looked up in the language table
+	pub fn setLocalized(&mut self, localized: bool)
+	{
+		self.localized = localized;
+		self.setString(self.source.clone());
 	}
 
 	pub fn setSize(&mut self, size: f32)
@@ -240,13 +889,13 @@ impl Text
 
 	pub fn getDimensions(&mut self) -> glam::Vec2
 	{
-		if self.update { self.reload(); }
+		if self.isOutdated() { self.reload(); }
 		self.dimensions
 	}
 
 	pub fn getBounds(&mut self) -> glam::Vec4
 	{
-		if self.update { self.reload(); }
+		if self.isOutdated() { self.reload(); }
 
 		let p1 = self.ts.getMatrix() * glam::vec4(0.0, 0.0, 0.0, 1.0);
 		let p2 = self.ts.getMatrix() * glam::vec4(self.dimensions.x, 0.0, 0.0, 1.0);
@@ -259,6 +908,57 @@ impl Text
 		glam::vec4(min.x, min.y, max.x - min.x, max.y - min.y)
 	}
 
+	pub fn measure(&mut self, s: &str) -> f32
+	{
+		let font = match self.getFont()
+		{
+			Some(f) => f,
+			None => return 0.0
+		};
+		font.prepare(s);
+		if font.height == 0.0 { return 0.0; }
+		let mut w = 0.0;
+		let mut prev = None;
+		for c in s.chars()
+		{
+			if let Some(g) = font.glyphs.get(&c)
+			{
+				w += g.advance + prev.map(|p| font.kern(p, c)).unwrap_or(0.0);
+				prev = Some(c);
+			}
+		}
+		w * self.size / font.height
+	}
+
+	// Index of the character boundary nearest to x, measured the same way as measure()
+	pub fn indexAt(&mut self, s: &str, x: f32) -> usize
+	{
+		let font = match self.getFont()
+		{
+			Some(f) => f,
+			None => return 0
+		};
+		font.prepare(s);
+		if font.height == 0.0 { return 0; }
+		let k = self.size / font.height;
+		let mut w = 0.0;
+		let mut prev = None;
+		for (i, c) in s.chars().enumerate()
+		{
+			let mut next = w;
+			if let Some(g) = font.glyphs.get(&c)
+			{
+				next += g.advance + prev.map(|p| font.kern(p, c)).unwrap_or(0.0);
+				prev = Some(c);
+			}
+			if x < (w + next) / 2.0 * k { return i; }
+			w = next;
+		}
+		s.chars().count()
+	}
+
+	pub fn getSize(&self) -> f32 { self.size }
+
 	pub fn getString(&self) -> String { self.text.clone() }
 
 	pub fn setColor(&mut self, clr: glam::Vec4) { self.color = clr; }
@@ -272,21 +972,24 @@ impl Drawable for Text
 {
 	fn draw(&mut self)
 	{
-		if self.update { self.reload(); }
+		if self.isOutdated() { self.reload(); }
 		if self.vertices == 0 { return; }
 		let s = Window::getCamera().activateShader(String::from("text"));
 		s.activate();
 		s.setInt("tex", 0);
-		s.setMat4("model", self.ts.getMatrix());
-		s.setVec4("clr", self.color);
+		let cam = Window::getCamera();
+		s.setMat4("model", cam.getParent() * self.ts.getMatrix());
+		s.setVec4("clr", self.color * glam::vec4(1.0, 1.0, 1.0, cam.getOpacity()));
 		unsafe
 		{
 			Window::getCamera().bindVAO(self.vao);
 			gl::ActiveTexture(gl::TEXTURE0);
-			gl::BindTexture(gl::TEXTURE_2D, self.font.texture);
-			gl::DrawArrays(gl::QUADS,
-				0, self.vertices
-			);
+			for (tex, first, count) in &self.batches
+			{
+				if *count == 0 { continue; }
+				gl::BindTexture(gl::TEXTURE_2D, *tex);
+				gl::DrawArrays(gl::QUADS, *first, *count);
+			}
 		}
 	}
 }
@@ -0,0 +1,372 @@
+use glfw::{Action, Key, Modifiers};
+
+use crate::ae2d::{Camera::Drawable, Shapes::Rectangle, Text::Text, Window::{Event, Window}};
+
+pub struct TextField
+{
+	text: Text,
+	// Drawn instead of the empty text, kept apart so neither is laid out again every frame
+	hint: Text,
+	value: Vec<char>,
+	caret: usize,
+	anchor: usize,
+	focused: bool,
+	dragging: bool,
+	maxLength: usize,
+	mask: Option<char>,
+	width: f32,
+	blink: f32,
+	changed: bool,
+	submitted: bool,
+	color: glam::Vec4,
+	selectionColor: glam::Vec4
+}
+
+impl TextField
+{
+	pub fn new() -> Self
+	{
+		let mut text = Text::new();
+		text.setMarkup(false);
+		text.setLocalized(false);
+		let mut hint = Text::new();
+		hint.setMarkup(false);
+		Self
+		{
+			text,
+			hint,
+			value: vec![],
+			caret: 0,
+			anchor: 0,
+			focused: false,
+			dragging: false,
+			maxLength: 0,
+			mask: None,
+			width: 0.0,
+			blink: 0.0,
+			changed: false,
+			submitted: false,
+			color: glam::Vec4::ONE,
+			selectionColor: glam::vec4(0.2, 0.4, 0.8, 0.5)
+		}
+	}
+
+	pub fn parse(node: &json::JsonValue) -> Self
+	{
+		let mut tf = Self::new();
+
+		for (x, y) in node.entries()
+		{
+			if x == "font"
+			{
+				tf.text.setFont(y.as_str().unwrap().to_string());
+				tf.hint.setFont(y.as_str().unwrap().to_string());
+			}
+			if x == "size"
+			{
+				tf.text.setSize(y.as_f32().unwrap());
+				tf.hint.setSize(y.as_f32().unwrap());
+			}
+			if x == "text" { tf.setString(y.as_str().unwrap().to_string()); }
+			if x == "placeholder" { tf.hint.setString(y.as_str().unwrap().to_string()); }
+			if x == "width" { tf.width = y.as_f32().unwrap(); }
+			if x == "maxLength" { tf.maxLength = y.as_usize().unwrap(); }
+			if x == "password" && y.as_bool().unwrap_or(false) { tf.mask = Some('*'); }
+			if x == "mask" { tf.mask = y.as_str().unwrap().chars().next(); }
+		}
+
+		tf.changed = false;
+		tf
+	}
+
+	fn displayed(&self, from: usize, to: usize) -> String
+	{
+		match self.mask
+		{
+			Some(m) => std::iter::repeat_n(m, to - from).collect(),
+			None => self.value[from..to].iter().collect()
+		}
+	}
+
+	fn selection(&self) -> (usize, usize)
+	{
+		(self.caret.min(self.anchor), self.caret.max(self.anchor))
+	}
+
+	fn selected(&self) -> String
+	{
+		let (from, to) = self.selection();
+		self.value[from..to].iter().collect()
+	}
+
+	fn removeSelection(&mut self) -> bool
+	{
+		let (from, to) = self.selection();
+		if from == to { return false; }
+		self.value.drain(from..to);
+		self.caret = from;
+		self.anchor = from;
+		true
+	}
+
+	fn insert(&mut self, s: &str)
+	{
+		let mut changed = self.removeSelection();
+		for c in s.chars().filter(|c| !c.is_control())
+		{
+			if self.maxLength > 0 && self.value.len() >= self.maxLength { break; }
+			self.value.insert(self.caret, c);
+			self.caret += 1;
+			changed = true;
+		}
+		self.anchor = self.caret;
+		if changed { self.changed = true; }
+	}
+
+	fn prevWord(&self, mut i: usize) -> usize
+	{
+		while i > 0 && self.value[i - 1].is_whitespace() { i -= 1; }
+		while i > 0 && !self.value[i - 1].is_whitespace() { i -= 1; }
+		i
+	}
+
+	fn nextWord(&self, mut i: usize) -> usize
+	{
+		let n = self.value.len();
+		while i < n && !self.value[i].is_whitespace() { i += 1; }
+		while i < n && self.value[i].is_whitespace() { i += 1; }
+		i
+	}
+
+	fn moveCaret(&mut self, pos: usize, select: bool)
+	{
+		self.caret = pos.min(self.value.len());
+		if !select { self.anchor = self.caret; }
+	}
+
+	fn key(&mut self, key: Key, mods: Modifiers)
+	{
+		let ctrl = mods.contains(Modifiers::Control);
+		let shift = mods.contains(Modifiers::Shift);
+		let w = Window::getInstance().window.as_mut().unwrap();
+
+		match key
+		{
+			Key::Left =>
+			{
+				let (from, to) = self.selection();
+				let pos = if ctrl { self.prevWord(self.caret) }
+					else if !shift && from != to { from }
+					else { self.caret.saturating_sub(1) };
+				self.moveCaret(pos, shift);
+			}
+			Key::Right =>
+			{
+				let (from, to) = self.selection();
+				let pos = if ctrl { self.nextWord(self.caret) }
+					else if !shift && from != to { to }
+					else { self.caret + 1 };
+				self.moveCaret(pos, shift);
+			}
+			Key::Home => self.moveCaret(0, shift),
+			Key::End => self.moveCaret(self.value.len(), shift),
+			Key::Backspace =>
+			{
+				if self.removeSelection() { self.changed = true; }
+				else if self.caret > 0
+				{
+					let from = if ctrl { self.prevWord(self.caret) } else { self.caret - 1 };
+					self.value.drain(from..self.caret);
+					self.moveCaret(from, false);
+					self.changed = true;
+				}
+			}
+			Key::Delete =>
+			{
+				if self.removeSelection() { self.changed = true; }
+				else if self.caret < self.value.len()
+				{
+					let to = if ctrl { self.nextWord(self.caret) } else { self.caret + 1 };
+					self.value.drain(self.caret..to);
+					self.changed = true;
+				}
+			}
+			Key::A if ctrl =>
+			{
+				self.anchor = 0;
+				self.caret = self.value.len();
+			}
+			Key::C if ctrl && self.mask.is_none() =>
+			{
+				w.set_clipboard_string(&self.selected());
+			}
+			Key::X if ctrl && self.mask.is_none() =>
+			{
+				w.set_clipboard_string(&self.selected());
+				if self.removeSelection() { self.changed = true; }
+			}
+			Key::V if ctrl =>
+			{
+				let s = w.get_clipboard_string().unwrap_or_default();
+				self.insert(&s);
+			}
+			Key::Enter | Key::KpEnter => self.submitted = true,
+			Key::Escape => self.setFocus(false),
+			_ => {}
+		}
+	}
+
+	fn indexAt(&mut self, x: f32, scale: f32) -> usize
+	{
+		if scale == 0.0 { return 0; }
+		let s = self.displayed(0, self.value.len());
+		self.text.indexAt(&s, x / scale)
+	}
+
+	// Mouse position is given in the coordinates of the owning object
+	pub fn update(&mut self, mouse: glam::Vec2)
+	{
+		self.changed = false;
+		self.submitted = false;
+		self.blink += Window::getDeltaTime();
+
+		let b = self.getBounds();
+		let inside = mouse.x >= b.x && mouse.x <= b.x + b.z &&
+			mouse.y >= b.y && mouse.y <= b.y + b.w;
+
+		for e in Window::getInstance().eventQueue.clone()
+		{
+			match e
+			{
+				// Focus itself is given by the UI to the topmost object under the cursor
+				Event::Mouse(glfw::MouseButton::Button1, Action::Press, mods) if self.focused && inside =>
+				{
+					let scale = self.text.getTransformable().getScale().x;
+					let i = self.indexAt(mouse.x - b.x, scale);
+					self.moveCaret(i, mods.contains(Modifiers::Shift));
+					self.dragging = true;
+					self.blink = 0.0;
+				}
+				Event::Mouse(glfw::MouseButton::Button1, Action::Release, _) => self.dragging = false,
+				Event::Char(c) if self.focused => self.insert(&c.to_string()),
+				Event::Key(k, Action::Press | Action::Repeat, mods) if self.focused =>
+				{
+					self.key(k, mods);
+					self.blink = 0.0;
+				}
+				_ => {}
+			}
+		}
+
+		// The anchor stays where the button was pressed
+		if self.dragging && self.focused
+		{
+			let scale = self.text.getTransformable().getScale().x;
+			let i = self.indexAt(mouse.x - b.x, scale);
+			self.moveCaret(i, true);
+		}
+
+		if self.changed
+		{
+			let s = self.displayed(0, self.value.len());
+			self.text.setString(s);
+		}
+	}
+
+	pub fn getBounds(&mut self) -> glam::Vec4
+	{
+		let ts = self.text.getTransformable();
+		let pos = ts.getPosition();
+		let scale = ts.getScale();
+		glam::vec4(pos.x, pos.y, self.width * scale.x, self.text.getSize() * scale.y)
+	}
+
+	pub fn setFocus(&mut self, focused: bool)
+	{
+		if focused && !self.focused { self.blink = 0.0; }
+		if !focused { self.dragging = false; }
+		self.focused = focused;
+	}
+
+	pub fn isFocused(&self) -> bool { self.focused }
+
+	pub fn isChanged(&self) -> bool { self.changed }
+
+	pub fn isSubmitted(&self) -> bool { self.submitted }
+
+	pub fn setString(&mut self, s: String)
+	{
+		self.value = s.chars().filter(|c| !c.is_control()).collect();
+		if self.maxLength > 0 { self.value.truncate(self.maxLength); }
+		self.moveCaret(self.value.len(), false);
+		self.text.setString(self.displayed(0, self.value.len()));
+		self.changed = true;
+	}
+
+	pub fn getString(&self) -> String { self.value.iter().collect() }
+
+	pub fn setMaxLength(&mut self, len: usize) { self.maxLength = len; }
+
+	pub fn setMask(&mut self, mask: Option<char>)
+	{
+		self.mask = mask;
+		self.text.setString(self.displayed(0, self.value.len()));
+	}
+
+	pub fn setPlaceholder(&mut self, s: String) { self.hint.setString(s); }
+
+	pub fn relocalize(&mut self) { self.hint.relocalize(); }
+
+	pub fn setWidth(&mut self, width: f32) { self.width = width; }
+
+	pub fn getWidth(&self) -> f32 { self.width }
+
+	pub fn setColor(&mut self, clr: glam::Vec4) { self.color = clr; }
+
+	pub fn setSelectionColor(&mut self, clr: glam::Vec4) { self.selectionColor = clr; }
+
+	pub fn getText(&mut self) -> &mut Text { &mut self.text }
+}
+
+impl Drawable for TextField
+{
+	fn draw(&mut self)
+	{
+		let b = self.getBounds();
+		let scale = self.text.getTransformable().getScale().x;
+		let (from, to) = self.selection();
+
+		if self.focused && from != to
+		{
+			let x1 = self.text.measure(&self.displayed(0, from)) * scale;
+			let x2 = self.text.measure(&self.displayed(0, to)) * scale;
+			let mut r = Rectangle::new();
+			r.getTransform().setPosition(glam::vec2(b.x + x1, b.y));
+			r.setSize(glam::vec2(x2 - x1, b.w));
+			r.setColor(self.selectionColor);
+			Window::getCamera().draw(&mut r);
+		}
+
+		if self.value.is_empty() && !self.focused
+		{
+			*self.hint.getTransformable() = self.text.getTransformable().clone();
+			self.hint.setColor(self.color * glam::vec4(1.0, 1.0, 1.0, 0.5));
+			Window::getCamera().draw(&mut self.hint);
+		}
+		else
+		{
+			self.text.setColor(self.color);
+			Window::getCamera().draw(&mut self.text);
+		}
+
+		if self.focused && self.blink % 1.0 < 0.5
+		{
+			let x = self.text.measure(&self.displayed(0, self.caret)) * scale;
+			let mut r = Rectangle::new();
+			r.getTransform().setPosition(glam::vec2(b.x + x, b.y));
+			r.setSize(glam::vec2(1.0_f32.max(scale), b.w));
+			r.setColor(self.color);
+			Window::getCamera().draw(&mut r);
+		}
+	}
+}
@@ -0,0 +1,277 @@
+use std::collections::VecDeque;
+
+use mlua::Function;
+
+use crate::ae2d::{Skeleton::Interpolation, UI::Object};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Part
+{
+	Node,
+	Sprite,
+	Text
+}
+
+impl From<&str> for Part
+{
+	fn from(s: &str) -> Self
+	{
+		match s
+		{
+			"sprite" => Part::Sprite,
+			"text" => Part::Text,
+			_ => Part::Node
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Prop
+{
+	X, Y,
+	Rotation,
+	ScaleX, ScaleY,
+	Alpha,
+	R, G, B
+}
+
+impl Prop
+{
+	// "scale" sets both axes
+	pub fn parse(name: &str) -> Option<Vec<Prop>>
+	{
+		Some(match name
+		{
+			"x" => vec![Prop::X],
+			"y" => vec![Prop::Y],
+			"rotation" => vec![Prop::Rotation],
+			"scale" => vec![Prop::ScaleX, Prop::ScaleY],
+			"scaleX" => vec![Prop::ScaleX],
+			"scaleY" => vec![Prop::ScaleY],
+			"alpha" => vec![Prop::Alpha],
+			"r" => vec![Prop::R],
+			"g" => vec![Prop::G],
+			"b" => vec![Prop::B],
+			_ => return None
+		})
+	}
+
+	// Properties that the layout sets every frame on objects that have one
+	pub fn isLaidOut(&self, part: Part) -> bool
+	{
+		match part
+		{
+			Part::Node => matches!(self, Prop::X | Prop::Y),
+			_ => matches!(self, Prop::X | Prop::Y | Prop::ScaleX | Prop::ScaleY)
+		}
+	}
+
+	fn get(&self, obj: &mut Object, part: Part) -> f32
+	{
+		let color = match part
+		{
+			Part::Node => glam::vec4(1.0, 1.0, 1.0, obj.getOpacity()),
+			Part::Sprite => obj.getSprite().getColor(),
+			Part::Text => obj.getText().getColor()
+		};
+		let ts = match part
+		{
+			Part::Node => obj.getTransformable(),
+			Part::Sprite => obj.getSprite().getTransformable(),
+			Part::Text => obj.getText().getTransformable()
+		};
+		match self
+		{
+			Prop::X => ts.getPosition().x,
+			Prop::Y => ts.getPosition().y,
+			Prop::Rotation => ts.getRotation(),
+			Prop::ScaleX => ts.getScale().x,
+			Prop::ScaleY => ts.getScale().y,
+			Prop::Alpha => color.w,
+			Prop::R => color.x,
+			Prop::G => color.y,
+			Prop::B => color.z
+		}
+	}
+
+	fn set(&self, obj: &mut Object, part: Part, v: f32)
+	{
+		let mut color = match part
+		{
+			Part::Node => glam::vec4(1.0, 1.0, 1.0, obj.getOpacity()),
+			Part::Sprite => obj.getSprite().getColor(),
+			Part::Text => obj.getText().getColor()
+		};
+		let ts = match part
+		{
+			Part::Node => obj.getTransformable(),
+			Part::Sprite => obj.getSprite().getTransformable(),
+			Part::Text => obj.getText().getTransformable()
+		};
+		let (pos, scale) = (ts.getPosition(), ts.getScale());
+		match self
+		{
+			Prop::X => ts.setPosition(glam::vec2(v, pos.y)),
+			Prop::Y => ts.setPosition(glam::vec2(pos.x, v)),
+			Prop::Rotation => ts.setRotation(v),
+			Prop::ScaleX => ts.setScale(glam::vec2(v, scale.y)),
+			Prop::ScaleY => ts.setScale(glam::vec2(scale.x, v)),
+			Prop::Alpha => color.w = v,
+			Prop::R => color.x = v,
+			Prop::G => color.y = v,
+			Prop::B => color.z = v
+		}
+		if !matches!(self, Prop::Alpha | Prop::R | Prop::G | Prop::B) { return; }
+		match part
+		{
+			Part::Node => obj.setOpacity(color.w),
+			Part::Sprite => obj.getSprite().setColorVec(color),
+			Part::Text => obj.getText().setColor(color)
+		}
+	}
+}
+
+// A single tween of an object, or a pause when there are no properties
+pub struct Step
+{
+	target: String,
+	part: Part,
+	props: Vec<(Prop, f32, f32)>,
+	duration: f32,
+	delay: f32,
+	easing: Interpolation,
+	elapsed: f32,
+	started: bool
+}
+
+impl Step
+{
+	pub fn new(target: String, part: Part, props: Vec<(Prop, f32)>, duration: f32, easing: Interpolation) -> Self
+	{
+		Self
+		{
+			target,
+			part,
+			props: props.into_iter().map(|(p, to)| (p, 0.0, to)).collect(),
+			duration,
+			delay: 0.0,
+			easing,
+			elapsed: 0.0,
+			started: false
+		}
+	}
+
+	pub fn wait(duration: f32) -> Self
+	{
+		Step::new(String::new(), Part::Node, vec![], duration, Interpolation::Linear)
+	}
+
+	pub fn setDelay(&mut self, delay: f32) { self.delay = delay; }
+}
+
+struct Track
+{
+	id: u32,
+	owner: String,
+	steps: VecDeque<Step>,
+	onDone: Option<Function>
+}
+
+pub struct Tweens
+{
+	tracks: Vec<Track>,
+	next: u32
+}
+
+impl Tweens
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			tracks: vec![],
+			next: 1
+		}
+	}
+
+	// Steps are played one after another, owner is the path of the object that started them
+	pub fn add(&mut self, owner: String, steps: Vec<Step>, onDone: Option<Function>) -> u32
+	{
+		let id = self.next;
+		self.next += 1;
+		self.tracks.push(Track { id, owner, steps: steps.into(), onDone });
+		id
+	}
+
+	pub fn cancel(&mut self, id: u32)
+	{
+		self.tracks.retain(|t| t.id != id);
+	}
+
+	// Cancels every tween started by the owner, or every tween of the target if it's given
+	pub fn cancelAll(&mut self, owner: &str, target: Option<(&str, Part)>)
+	{
+		self.tracks.retain(|t| match target
+		{
+			Some((path, part)) => !t.steps.iter().any(|s| s.target == path && s.part == part),
+			None => t.owner != owner
+		});
+	}
+
+	pub fn isActive(&self, id: u32) -> bool
+	{
+		self.tracks.iter().any(|t| t.id == id)
+	}
+
+	pub fn clear(&mut self) { self.tracks.clear(); }
+
+	// Returns callbacks of the finished tweens, they are called by the UI after the update
+	pub fn update(&mut self, dt: f32, objects: &mut [Object]) -> Vec<Function>
+	{
+		let mut done = vec![];
+		for track in &mut self.tracks
+		{
+			let mut dt = dt;
+			while let Some(step) = track.steps.front_mut()
+			{
+				if step.delay > 0.0
+				{
+					let d = step.delay.min(dt);
+					step.delay -= d;
+					dt -= d;
+					if step.delay > 0.0 { break; }
+				}
+
+				let obj = objects.iter_mut().find(|o| !step.target.is_empty() && o.getPath() == step.target);
+				step.elapsed += dt;
+				let t = if step.duration > 0.0 { (step.elapsed / step.duration).min(1.0) } else { 1.0 };
+
+				if let Some(obj) = obj
+				{
+					if !step.started
+					{
+						for (p, from, _) in &mut step.props { *from = p.get(obj, step.part); }
+					}
+					let k = if t >= 1.0 { 1.0 } else { step.easing.ease(t) };
+					for (p, from, to) in &step.props
+					{
+						p.set(obj, step.part, from + (to - from) * k);
+					}
+				}
+				step.started = true;
+
+				if t < 1.0 { break; }
+				dt = (step.elapsed - step.duration).max(0.0);
+				track.steps.pop_front();
+			}
+		}
+
+		self.tracks.retain_mut(|t|
+		{
+			if !t.steps.is_empty() { return true; }
+			if let Some(f) = t.onDone.take() { done.push(f); }
+			false
+		});
+		done
+	}
+}
@@ -1,15 +1,33 @@
-use mlua::{Function, Lua, Value};
+use glfw::{Action, Key, Modifiers, MouseButton};
+use mlua::{Function, IntoLuaMulti, Lua, Value};
 
-use crate::ae2d::Window::Window;
+use crate::ae2d::Window::{Event, Window};
 
-use super::{bind, Camera::Drawable, Sprite::Sprite, Text::Text};
+use super::{bind, Camera::Drawable, Layout::{Container, Layout, Length}, Sprite::Sprite, Text::{Align, Text}, TextField::TextField, Transformable::Transformable2D, Tween::Tweens, Widget::{Kind, Signal, Widget}};
 
 pub struct Object
 {
 	name: String,
+	path: String,
+	parent: Option<usize>,
 	script: Lua,
+	scriptPath: String,
 	spr: Sprite,
-	text: Text
+	text: Text,
+	field: Option<TextField>,
+	widget: Option<Widget>,
+	layout: Option<Layout>,
+	ts: Transformable2D,
+	visible: bool,
+	enabled: bool,
+	opacity: f32,
+	interactive: bool,
+	focusable: bool,
+	// Values composed with every parent, updated by UI::propagate
+	world: glam::Mat4,
+	shown: bool,
+	active: bool,
+	alpha: f32
 }
 
 impl Object
@@ -19,20 +37,32 @@ impl Object
 		Self
 		{
 			name: String::new(),
+			path: String::new(),
+			parent: None,
 			script: Lua::new(),
+			scriptPath: String::new(),
 			spr: Sprite::default(),
-			text: Text::new()
+			text: Text::new(),
+			field: None,
+			widget: None,
+			layout: None,
+			ts: Transformable2D::new(),
+			visible: true,
+			enabled: true,
+			opacity: 1.0,
+			interactive: true,
+			focusable: false,
+			world: glam::Mat4::IDENTITY,
+			shown: true,
+			active: true,
+			alpha: 1.0
 		}
 	}
-	pub fn parse(node: &json::JsonValue) -> Self
+
+	// Children get their parent's path as a prefix: "inventory/slot3"
+	pub fn parse(node: &json::JsonValue, prefix: &str) -> Self
 	{
-		let mut obj = Self
-		{
-			name: String::new(),
-			script: Lua::new(),
-			spr: Sprite::default(),
-			text: Text::new()
-		};
+		let mut obj = Self::new();
 
 		bind::sprite(&obj.script);
 		bind::text(&obj.script);
@@ -41,8 +71,19 @@ impl Object
 		bind::network(&obj.script);
 		bind::shapes(&obj.script);
 		bind::profiler(&obj.script);
+		bind::audio(&obj.script);
+		bind::input(&obj.script);
+		bind::gamepad(&obj.script);
+		bind::textField(&obj.script);
+		bind::loc(&obj.script);
+		bind::resources(&obj.script);
+		bind::layout(&obj.script);
+		bind::node(&obj.script);
+		bind::widget(&obj.script);
+		bind::tween(&obj.script);
 
 		let mut f = None;
+		let mut focusable = None;
 
 		for (var, value) in node.entries()
 		{
@@ -52,6 +93,7 @@ impl Object
 			}
 			if var == "script"
 			{
+				obj.scriptPath = value.as_str().unwrap().to_string();
 				f = Some(obj.script.load(
 					std::fs::read_to_string(
 						value.as_str().unwrap()
@@ -92,8 +134,45 @@ impl Object
 							y.as_str().unwrap().to_string()
 						);
 					}
+					if x == "maxWidth" { obj.text.setMaxWidth(y.as_f32().unwrap()); }
+					if x == "align" { obj.text.setAlign(Align::from(y.as_str().unwrap())); }
+					if x == "lineSpacing" { obj.text.setLineSpacing(y.as_f32().unwrap()); }
+					if x == "maxLines" { obj.text.setMaxLines(y.as_usize().unwrap()); }
+					if x == "ellipsis" { obj.text.setEllipsis(y.as_bool().unwrap()); }
 				}
 			}
+			if var == "visible" { obj.visible = value.as_bool().unwrap_or(true); }
+			if var == "enabled" { obj.enabled = value.as_bool().unwrap_or(true); }
+			if var == "opacity" { obj.opacity = value.as_f32().unwrap_or(1.0); }
+			if var == "interactive" { obj.interactive = value.as_bool().unwrap_or(true); }
+			if var == "focusable" { focusable = value.as_bool(); }
+			if var == "position"
+			{
+				obj.ts.setPosition(glam::vec2(
+					value[0].as_f32().unwrap_or(0.0),
+					value[1].as_f32().unwrap_or(0.0)
+				));
+			}
+			if var == "rotation" { obj.ts.setRotation(value.as_f32().unwrap_or(0.0)); }
+			if var == "scale"
+			{
+				obj.ts.setScale(glam::vec2(
+					value[0].as_f32().unwrap_or(1.0),
+					value[1].as_f32().unwrap_or(1.0)
+				));
+			}
+			if var == "layout"
+			{
+				obj.layout = Some(Layout::parse(value));
+			}
+			if var == "textField"
+			{
+				obj.field = Some(TextField::parse(value));
+			}
+			if var == "widget"
+			{
+				obj.widget = Some(Widget::parse(value));
+			}
 			if var == "vars"
 			{
 				let t = obj.script.create_table().unwrap();
@@ -117,12 +196,16 @@ impl Object
 			}
 		}
 
+		let widget = obj.widget.as_ref().is_some_and(|w| w.getKind() != Kind::Progress);
+		obj.focusable = focusable.unwrap_or(obj.field.is_some() || widget);
+		obj.path = if prefix.is_empty() { obj.name.clone() } else { format!("{prefix}/{}", obj.name) };
+
 		if let Some(func) = f
 		{
 			let _ = obj.script.load_std_libs(mlua::StdLib::ALL_SAFE);
 			let _ = obj.script.globals().set(
 				"ScriptID",
-				String::from("ui_") + &obj.name
+				String::from("ui_") + &obj.path
 			);
 			let _ = func.exec();
 		}
@@ -140,14 +223,198 @@ impl Object
 		&mut self.text
 	}
 
+	pub fn getTextField(&mut self) -> Option<&mut TextField>
+	{
+		self.field.as_mut()
+	}
+
+	pub fn getLayout(&mut self) -> Option<&mut Layout>
+	{
+		self.layout.as_mut()
+	}
+
+	pub fn getWidget(&mut self) -> Option<&mut Widget>
+	{
+		self.widget.as_mut()
+	}
+
+	// Size of the sprite, text or text field with padding, used for "auto" sizes
+	fn contentSize(&mut self, scale: f32) -> glam::Vec2
+	{
+		let mut size = self.text.getDimensions();
+		if self.spr.getTexture() != 0 { size = size.max(self.spr.getFrameSize()); }
+		if let Some(f) = &mut self.field
+		{
+			size = size.max(glam::vec2(f.getWidth(), f.getText().getSize()));
+		}
+		if let Some(w) = &self.widget { size = size.max(w.getBaseSize()); }
+		let p = self.layout.as_ref().map(|l| l.padding).unwrap_or_default();
+		(size + glam::vec2(p.x + p.z, p.y + p.w)) * scale
+	}
+
+	// The object is moved to the rectangle, its sprite and text are placed inside of it
+	fn applyLayout(&mut self, scale: f32)
+	{
+		let l = match &self.layout { Some(l) => l, None => return };
+		let rect = l.rect;
+		let p = l.padding * scale;
+		let inner = glam::vec4(p.x, p.y, rect.z - p.x - p.z, rect.w - p.y - p.w);
+		let fixedWidth = l.size.0 != Length::Auto;
+
+		self.ts.setPosition(glam::vec2(rect.x, rect.y));
+
+		if self.spr.getTexture() != 0
+		{
+			// Nine-slice corners are scaled with the interface instead of the rectangle
+			let sliced = self.spr.isSliced();
+			if sliced { self.spr.setSize(glam::vec2(rect.z, rect.w) / scale); }
+			let fs = self.spr.getFrameSize();
+			let ts = self.spr.getTransformable();
+			if sliced { ts.setScale(glam::Vec2::splat(scale)); }
+			else if fs.x > 0.0 && fs.y > 0.0
+			{
+				ts.setScale(glam::vec2(rect.z, rect.w) / fs);
+			}
+			let origin = ts.getOrigin() * ts.getScale();
+			ts.setPosition(origin);
+		}
+
+		let ts = self.text.getTransformable();
+		ts.setPosition(glam::vec2(inner.x, inner.y));
+		ts.setScale(glam::Vec2::splat(scale));
+		if fixedWidth { self.text.setMaxWidth(inner.z / scale); }
+
+		if let Some(f) = &mut self.field
+		{
+			let ts = f.getText().getTransformable();
+			ts.setPosition(glam::vec2(inner.x, inner.y));
+			ts.setScale(glam::Vec2::splat(scale));
+			if fixedWidth { f.setWidth(inner.z / scale); }
+		}
+
+		if let Some(w) = &mut self.widget { w.setRect(glam::vec2(rect.z, rect.w), scale); }
+	}
+
 	pub fn getScript(&self) -> &mlua::Lua { &self.script }
+
+	// New code runs in the same Lua state, so global variables keep their values
+	pub fn reloadScript(&mut self)
+	{
+		let src = match std::fs::read_to_string(&self.scriptPath)
+		{
+			Ok(x) => x,
+			Err(x) => { println!("Failed to reload script {}: {x}", self.scriptPath); return; }
+		};
+		if let Err(x) = self.script.load(src).set_name(format!("@{}", self.scriptPath)).exec()
+		{
+			println!("Failed to reload script {}:\n{x}\n", self.scriptPath);
+			return;
+		}
+		println!("Reloaded script {}", self.scriptPath);
+		self.event("OnReload", ());
+	}
+
+	pub fn getTransformable(&mut self) -> &mut Transformable2D { &mut self.ts }
+
+	pub fn getPath(&self) -> String { self.path.clone() }
+
+	pub fn setVisible(&mut self, visible: bool) { self.visible = visible; }
+	pub fn isVisible(&self) -> bool { self.visible }
+
+	pub fn setEnabled(&mut self, enabled: bool) { self.enabled = enabled; }
+	pub fn isEnabled(&self) -> bool { self.enabled }
+
+	pub fn setOpacity(&mut self, opacity: f32) { self.opacity = opacity.clamp(0.0, 1.0); }
+	pub fn getOpacity(&self) -> f32 { self.opacity }
+
+	// Cursor position in the object's own coordinates
+	pub fn toLocal(&self, pos: glam::Vec2) -> glam::Vec2
+	{
+		self.world.inverse().transform_point3(glam::vec3(pos.x, pos.y, 0.0)).truncate()
+	}
+
+	// Area that receives the mouse in the object's own coordinates
+	fn hitBounds(&mut self) -> Option<glam::Vec4>
+	{
+		let mut list = vec![];
+		if let Some(l) = &self.layout { list.push(glam::vec4(0.0, 0.0, l.rect.z, l.rect.w)); }
+		else
+		{
+			if self.spr.getTexture() != 0 { list.push(self.spr.getBounds()); }
+			if !self.text.getString().is_empty() { list.push(self.text.getBounds()); }
+			if let Some(f) = &mut self.field { list.push(f.getBounds()); }
+			if let Some(w) = &self.widget { list.push(w.getBounds()); }
+		}
+		if let Some(r) = self.widget.as_ref().and_then(|w| w.getOverlay()) { list.push(r); }
+
+		list.into_iter().reduce(|a, b|
+		{
+			let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+			glam::vec4(x, y, (a.x + a.z).max(b.x + b.z) - x, (a.y + a.w).max(b.y + b.w) - y)
+		})
+	}
+
+	// Opened dropdowns are above every other object
+	fn overlayContains(&self, pos: glam::Vec2) -> bool
+	{
+		let p = self.toLocal(pos);
+		self.widget.as_ref().and_then(|w| w.getOverlay()).is_some_and(|b|
+			p.x >= b.x && p.x <= b.x + b.z &&
+			p.y >= b.y && p.y <= b.y + b.w
+		)
+	}
+
+	fn contains(&mut self, pos: glam::Vec2) -> bool
+	{
+		let p = self.toLocal(pos);
+		self.hitBounds().is_some_and(|b|
+			p.x >= b.x && p.x <= b.x + b.z &&
+			p.y >= b.y && p.y <= b.y + b.w
+		)
+	}
+
+	fn event(&self, name: &str, args: impl IntoLuaMulti)
+	{
+		if let Ok(f) = self.script.globals().get::<Function>(name)
+		{
+			if let Err(x) = f.call::<Value>(args)
+			{
+				println!("Object {name}: {}\n{x}\n", self.path);
+			}
+		}
+	}
+}
+
+// Copies plain data between Lua states, functions and userdata become nil
+fn copyValue(v: &Value, to: &Lua, depth: u8) -> Value
+{
+	match v
+	{
+		Value::Boolean(_) | Value::Integer(_) | Value::Number(_) => v.clone(),
+		Value::String(x) => to.create_string(&*x.as_bytes()).map(Value::String).unwrap_or(Value::Nil),
+		Value::Table(t) if depth < 16 =>
+		{
+			let copy = match to.create_table() { Ok(x) => x, Err(_) => return Value::Nil };
+			for (k, v) in t.pairs::<Value, Value>().flatten()
+			{
+				let _ = copy.raw_set(copyValue(&k, to, depth + 1), copyValue(&v, to, depth + 1));
+			}
+			Value::Table(copy)
+		}
+		_ => Value::Nil
+	}
 }
 
 pub struct UI
 {
+	path: String,
 	baseSize: glam::Vec2,
 	objects: Vec<Object>,
-	reload: String
+	reload: String,
+	hovered: Option<usize>,
+	focused: Option<usize>,
+	pressed: Vec<(MouseButton, usize)>,
+	tweens: Tweens
 }
 
 impl UI
@@ -156,9 +423,14 @@ impl UI
 	{
 		Self
 		{
+			path: String::new(),
 			baseSize: glam::Vec2::ZERO,
 			objects: vec![],
-			reload: String::new()
+			reload: String::new(),
+			hovered: None,
+			focused: None,
+			pressed: vec![],
+			tweens: Tweens::new()
 		}
 	}
 
@@ -175,23 +447,49 @@ impl UI
 		);
 		if src.is_err()
 		{
-			println!("Failed to load UI: {}", src.unwrap_err());
+			println!("Failed to load UI from {path}: {}", src.unwrap_err());
 			return;
 		}
 		let src = src.unwrap();
 
-		self.objects.clear();
+		// The "state" table of every script is kept when the same UI is loaded again
+		let reload = self.path == path;
+		let old = std::mem::take(&mut self.objects);
+		self.path = path.to_string();
+		self.hovered = None;
+		self.focused = None;
+		self.pressed.clear();
+		self.tweens.clear();
 
+		let mut top = vec![];
 		for (name, value) in src.entries()
 		{
 			let id: usize = name.parse().expect(&format!("Wrong UI object ID: {name}"));
-			if id + 1 > self.objects.len()
+			top.push((id, value));
+		}
+		top.sort_by_key(|x| x.0);
+
+		for (_, value) in top
+		{
+			self.addObject(value, None);
+		}
+
+		if reload
+		{
+			for obj in &self.objects
 			{
-				self.objects.resize_with(id + 1, || Object::new());
+				let state = match old.iter().find(|o| o.path == obj.path)
+				{
+					Some(o) => o.script.globals().raw_get::<Value>("state").unwrap_or(Value::Nil),
+					None => continue
+				};
+				if state.is_nil() { continue; }
+				let _ = obj.script.globals().raw_set("state", copyValue(&state, &obj.script, 0));
 			}
-
-			self.objects[id] = Object::parse(value);
 		}
+		drop(old);
+
+		self.relayout();
 
 		for obj in &self.objects
 		{
@@ -211,18 +509,137 @@ impl UI
 		}
 	}
 
+	// Objects are stored in depth-first order, so parents always come before their children
+	fn addObject(&mut self, node: &json::JsonValue, parent: Option<usize>)
+	{
+		let prefix = parent.map(|p| self.objects[p].path.clone()).unwrap_or_default();
+		let mut obj = Object::parse(node, &prefix);
+		obj.parent = parent;
+		let id = self.objects.len();
+		self.objects.push(obj);
+
+		for child in node["children"].members()
+		{
+			self.addObject(child, Some(id));
+		}
+	}
+
 	pub fn getObject(&mut self, name: String) -> &mut Object
 	{
 		for o in &mut self.objects
 		{
-			if o.name == name { return o; }
+			if o.path == name { return o; }
 		}
 		panic!("UI object '{name}' not found");
 	}
 
+	pub fn findObject(&mut self, path: &str) -> Option<&mut Object>
+	{
+		self.objects.iter_mut().find(|o| o.path == path)
+	}
+
+	pub fn getChildren(&self, path: &str) -> Vec<String>
+	{
+		let id = self.objects.iter().position(|o| o.path == path);
+		self.objects.iter()
+			.filter(|o| id.is_some() && o.parent == id)
+			.map(|o| o.path.clone())
+			.collect()
+	}
+
+	pub fn getParent(&self, path: &str) -> Option<String>
+	{
+		let o = self.objects.iter().find(|o| o.path == path)?;
+		o.parent.map(|p| self.objects[p].path.clone())
+	}
+
+	// Composes transforms, visibility, enable state and opacity with the parents
+	fn propagate(&mut self)
+	{
+		for i in 0..self.objects.len()
+		{
+			let (world, shown, active, alpha) = match self.objects[i].parent
+			{
+				Some(p) =>
+				{
+					let o = &self.objects[p];
+					(o.world, o.shown, o.active, o.alpha)
+				}
+				None => (glam::Mat4::IDENTITY, true, true, 1.0)
+			};
+			let o = &mut self.objects[i];
+			o.world = world * o.ts.getMatrix();
+			o.shown = shown && o.visible;
+			o.active = active && o.enabled;
+			o.alpha = alpha * o.opacity;
+		}
+	}
+
 	pub fn update(&mut self)
 	{
 		Window::getProfiler().restart();
+		self.propagate();
+		let (mx, my) = Window::getInstance().window.as_ref().unwrap().get_cursor_pos();
+		self.syncFocus();
+		self.route(glam::vec2(mx as f32, my as f32));
+		for obj in &mut self.objects
+		{
+			let mouse = obj.toLocal(glam::vec2(mx as f32, my as f32));
+			let usable = obj.shown && obj.active;
+			if let Some(field) = &mut obj.field
+			{
+				if !usable { field.setFocus(false); continue; }
+				field.update(mouse);
+				let value = field.getString();
+				if field.isChanged()
+				{
+					if let Ok(f) = obj.script.globals().get::<Function>("OnTextChanged")
+					{
+						if let Err(x) = f.call::<Value>(value.clone())
+						{
+							println!("Object OnTextChanged: {}\n{x}\n", obj.name);
+						}
+					}
+				}
+				if field.isSubmitted()
+				{
+					if let Ok(f) = obj.script.globals().get::<Function>("OnSubmit")
+					{
+						if let Err(x) = f.call::<Value>(value)
+						{
+							println!("Object OnSubmit: {}\n{x}\n", obj.name);
+						}
+					}
+				}
+			}
+		}
+		for i in 0..self.objects.len()
+		{
+			let hovered = self.hovered == Some(i);
+			let focused = self.focused == Some(i);
+			let obj = &mut self.objects[i];
+			let mouse = obj.toLocal(glam::vec2(mx as f32, my as f32));
+			let usable = obj.shown && obj.active;
+			let w = match &mut obj.widget { Some(w) => w, None => continue };
+			if !usable { w.reset(); continue; }
+			for signal in w.update(mouse, hovered, focused)
+			{
+				match signal
+				{
+					Signal::Toggle(x) => obj.event("OnToggle", x),
+					Signal::Value(x) => obj.event("OnValueChanged", x),
+					Signal::Select(n, x) => obj.event("OnSelect", (n + 1, x))
+				}
+			}
+		}
+		self.syncFocus();
+		for f in self.tweens.update(Window::getDeltaTime(), &mut self.objects)
+		{
+			if let Err(x) = f.call::<Value>(())
+			{
+				println!("Tween onDone:\n{x}\n");
+			}
+		}
 		for obj in &self.objects
 		{
 			let name = &obj.name;
@@ -239,9 +656,231 @@ impl UI
 				}
 			}
 		}
+		self.relayout();
 		Window::getProfiler().save("uiUpdate".to_string());
 	}
 
+	// Sends hover, click and keyboard focus events to the topmost object under the cursor
+	fn route(&mut self, mouse: glam::Vec2)
+	{
+		let usable = |o: &Object| o.shown && o.active && o.interactive;
+		let top = (0..self.objects.len()).rev()
+			.find(|i| usable(&self.objects[*i]) && self.objects[*i].overlayContains(mouse))
+			.or_else(|| (0..self.objects.len()).rev().find(|i|
+			{
+				let o = &mut self.objects[*i];
+				usable(o) && o.contains(mouse)
+			}));
+		if top != self.hovered
+		{
+			if let Some(i) = self.hovered { self.objects[i].event("OnLeave", ()); }
+			self.hovered = top;
+			if let Some(i) = top { self.objects[i].event("OnHover", ()); }
+		}
+
+		for e in Window::getInstance().eventQueue.clone()
+		{
+			match e
+			{
+				Event::Mouse(b, Action::Press, _) =>
+				{
+					if b == MouseButton::Button1
+					{
+						// Clicking a child of a focusable object focuses the object itself
+						let mut target = self.hovered;
+						while let Some(i) = target
+						{
+							if self.objects[i].focusable { break; }
+							target = self.objects[i].parent;
+						}
+						self.setFocus(target);
+					}
+					self.pressed.retain(|x| x.0 != b);
+					if let Some(i) = self.hovered { self.pressed.push((b, i)); }
+				}
+				Event::Mouse(b, Action::Release, _) =>
+				{
+					if let Some(n) = self.pressed.iter().position(|x| x.0 == b)
+					{
+						let (_, i) = self.pressed.remove(n);
+						if self.hovered == Some(i) { self.objects[i].event("OnClick", Window::mbToStr(b)); }
+					}
+				}
+				Event::Key(Key::Tab, Action::Press | Action::Repeat, mods) =>
+				{
+					self.cycleFocus(mods.contains(Modifiers::Shift));
+				}
+				Event::Key(Key::Enter | Key::KpEnter | Key::Space, Action::Press, _) =>
+				{
+					if let Some(i) = self.focused.filter(|i| self.objects[*i].field.is_none())
+					{
+						self.objects[i].event("OnClick", "Left");
+					}
+				}
+				_ => {}
+			}
+		}
+	}
+
+	fn setFocus(&mut self, id: Option<usize>)
+	{
+		if self.focused == id { return; }
+		let old = self.focused;
+		self.focused = id;
+		if let Some(i) = old
+		{
+			let o = &mut self.objects[i];
+			if let Some(f) = &mut o.field { f.setFocus(false); }
+			o.event("OnBlur", ());
+		}
+		if let Some(i) = id
+		{
+			let o = &mut self.objects[i];
+			if let Some(f) = &mut o.field { f.setFocus(true); }
+			o.event("OnFocus", ());
+		}
+	}
+
+	// Text fields can be focused from scripts or lose focus with Escape
+	fn syncFocus(&mut self)
+	{
+		if let Some(i) = self.focused
+		{
+			let o = &self.objects[i];
+			if !o.shown || !o.active || o.field.as_ref().is_some_and(|f| !f.isFocused())
+			{
+				self.setFocus(None);
+			}
+		}
+		let field = self.objects.iter().enumerate().position(|(i, o)|
+			Some(i) != self.focused && o.field.as_ref().is_some_and(|f| f.isFocused())
+		);
+		if field.is_some() { self.setFocus(field); }
+	}
+
+	fn cycleFocus(&mut self, back: bool)
+	{
+		let list: Vec<usize> = (0..self.objects.len())
+			.filter(|i| { let o = &self.objects[*i]; o.shown && o.active && o.focusable })
+			.collect();
+		if list.is_empty() { return; }
+
+		let n = list.len();
+		let next = match self.focused.and_then(|f| list.iter().position(|x| *x == f))
+		{
+			Some(p) if back => (p + n - 1) % n,
+			Some(p) => (p + 1) % n,
+			None if back => n - 1,
+			None => 0
+		};
+		self.setFocus(Some(list[next]));
+	}
+
+	pub fn focus(&mut self, path: &str)
+	{
+		let id = self.objects.iter().position(|o| o.path == path);
+		if id.is_some() { self.setFocus(id); }
+	}
+
+	pub fn blur(&mut self) { self.setFocus(None); }
+
+	pub fn getFocused(&self) -> Option<String>
+	{
+		self.focused.map(|i| self.objects[i].path.clone())
+	}
+
+	pub fn getHovered(&self) -> Option<String>
+	{
+		self.hovered.map(|i| self.objects[i].path.clone())
+	}
+
+	pub fn getScale(&self) -> f32
+	{
+		let (w, h) = Window::getSize();
+		if self.baseSize.x <= 0.0 || self.baseSize.y <= 0.0 { return 1.0; }
+		(w as f32 / self.baseSize.x).min(h as f32 / self.baseSize.y)
+	}
+
+	// Resolves rectangles of every object with a layout, top-level objects are placed on the screen
+	pub fn relayout(&mut self)
+	{
+		let (w, h) = Window::getSize();
+		let screen = glam::vec4(0.0, 0.0, w as f32, h as f32);
+		let scale = self.getScale();
+
+		let nested: Vec<String> = self.objects.iter()
+			.filter_map(|o| o.layout.as_ref())
+			.flat_map(|l| l.items.clone())
+			.collect();
+
+		for i in 0..self.objects.len()
+		{
+			let o = &self.objects[i];
+			if o.layout.is_none() || nested.contains(&o.path) { continue; }
+			if o.parent.is_some_and(|p| self.objects[p].layout.is_some()) { continue; }
+			self.place(i, screen, scale, 0);
+		}
+	}
+
+	// Children are placed in the parent's own coordinates, named items in the coordinates of its parent
+	fn place(&mut self, i: usize, parent: glam::Vec4, scale: f32, depth: u8)
+	{
+		if depth > 32 { println!("UI layout: '{}' is nested too deep", self.objects[i].path); return; }
+
+		let content = self.objects[i].contentSize(scale);
+		let l = self.objects[i].layout.as_mut().unwrap();
+		l.resolve(parent, scale, content);
+		let rect = l.rect;
+		let inner = l.content(scale) - glam::vec4(rect.x, rect.y, 0.0, 0.0);
+		let container = l.container;
+		let spacing = l.spacing * scale;
+		let cellHeight = l.cellHeight;
+		let names = l.items.clone();
+		self.objects[i].applyLayout(scale);
+
+		let mut items: Vec<(usize, glam::Vec2)> = self.objects.iter().enumerate()
+			.filter(|(_, o)| o.parent == Some(i) && o.layout.is_some())
+			.map(|(id, _)| (id, glam::Vec2::ZERO))
+			.collect();
+		items.extend(names.iter()
+			.filter_map(|n| self.objects.iter().position(|o| o.path == *n && o.layout.is_some()))
+			.map(|id| (id, glam::vec2(rect.x, rect.y)))
+		);
+
+		let full = glam::vec2(inner.z, inner.w);
+		let mut pos = glam::vec2(inner.x, inner.y);
+		let mut row = 0.0_f32;
+		for (n, (id, shift)) in items.into_iter().enumerate()
+		{
+			let c = self.objects[id].contentSize(scale);
+			let outer = self.objects[id].layout.as_ref().unwrap().outer(scale, full, c);
+			let cell = match container
+			{
+				Container::None => inner,
+				Container::VStack =>
+				{
+					pos.y += outer.y + spacing;
+					glam::vec4(inner.x, pos.y - outer.y - spacing, inner.z, outer.y)
+				}
+				Container::HStack =>
+				{
+					pos.x += outer.x + spacing;
+					glam::vec4(pos.x - outer.x - spacing, inner.y, outer.x, inner.w)
+				}
+				Container::Grid(columns) =>
+				{
+					let w = (inner.z - spacing * (columns - 1) as f32) / columns as f32;
+					let col = n % columns;
+					if col == 0 && n > 0 { pos.y += row + spacing; row = 0.0; }
+					let h = cellHeight.resolve(inner.w, scale, outer.y);
+					row = row.max(h);
+					glam::vec4(inner.x + col as f32 * (w + spacing), pos.y, w, h)
+				}
+			};
+			self.place(id, cell + glam::vec4(shift.x, shift.y, 0.0, 0.0), scale, depth + 1);
+		}
+	}
+
 	pub fn requestLoad(&mut self, path: String)
 	{
 		self.reload = path;
@@ -258,6 +897,7 @@ impl UI
 
 	pub fn resize(&mut self)
 	{
+		self.relayout();
 		for obj in &self.objects
 		{
 			if let Ok(f) = obj.script.globals().get::<Function>("OnResized")
@@ -267,7 +907,55 @@ impl UI
 		}
 	}
 	
+	pub fn gamepadEvent(&mut self, id: u8, connected: bool)
+	{
+		for obj in &self.objects
+		{
+			if let Ok(f) = obj.script.globals().get::<Function>("OnGamepad")
+			{
+				if let Err(x) = f.call::<Value>((id, connected))
+				{
+					println!("Object OnGamepad: {}\n{x}\n", obj.name);
+				}
+			}
+		}
+	}
+
+	pub fn relocalize(&mut self)
+	{
+		for obj in &mut self.objects
+		{
+			obj.text.relocalize();
+			if let Some(w) = &mut obj.widget { w.relocalize(); }
+			if let Some(f) = &mut obj.field { f.relocalize(); }
+		}
+		let code = Window::getLocale().getLanguage();
+		for obj in &self.objects
+		{
+			if let Ok(f) = obj.script.globals().get::<Function>("OnLanguageChanged")
+			{
+				if let Err(x) = f.call::<Value>(code.clone())
+				{
+					println!("Object OnLanguageChanged: {}\n{x}\n", obj.name);
+				}
+			}
+		}
+	}
+
 	pub fn getSize(&self) -> glam::Vec2 { self.baseSize }
+
+	pub fn getTweens(&mut self) -> &mut Tweens { &mut self.tweens }
+
+	pub fn getPath(&self) -> String { self.path.clone() }
+
+	// Pairs of object paths and their script files
+	pub fn getScripts(&self) -> Vec<(String, String)>
+	{
+		self.objects.iter()
+			.filter(|o| !o.scriptPath.is_empty())
+			.map(|o| (o.path.clone(), o.scriptPath.clone()))
+			.collect()
+	}
 }
 
 impl Drawable for UI
@@ -275,9 +963,13 @@ impl Drawable for UI
 	fn draw(&mut self)
 	{
 		Window::getProfiler().restart();
-		for obj in &self.objects
+		self.propagate();
+		for obj in &mut self.objects
 		{
-			let name = &obj.name;
+			if !obj.shown { continue; }
+			Window::getCamera().setParent(obj.world, obj.alpha);
+			if let Some(w) = &mut obj.widget { w.draw(obj.active); }
+			let name = &obj.path;
 			if let Ok(f) = obj.script.globals()
 				.get::<mlua::Function>("Draw")
 			{
@@ -291,6 +983,16 @@ impl Drawable for UI
 				}
 			}
 		}
+		for obj in &mut self.objects
+		{
+			if !obj.shown { continue; }
+			if let Some(w) = &mut obj.widget
+			{
+				Window::getCamera().setParent(obj.world, obj.alpha);
+				w.drawOverlay();
+			}
+		}
+		Window::getCamera().setParent(glam::Mat4::IDENTITY, 1.0);
 		unsafe { gl::Finish(); }
 		Window::getProfiler().save("uiDraw".to_string());
 	}
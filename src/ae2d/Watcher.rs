@@ -0,0 +1,91 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use crate::ae2d::Window::Window;
+
+// How often modification times are checked, in seconds
+const INTERVAL: f32 = 0.5;
+
+// Polls modification times of the active UI, its scripts, loaded textures and shaders
+pub struct Watcher
+{
+	enabled: bool,
+	timer: f32,
+	times: HashMap<String, SystemTime>
+}
+
+impl Watcher
+{
+	pub fn new() -> Self
+	{
+		Self
+		{
+			enabled: false,
+			timer: 0.0,
+			times: HashMap::new()
+		}
+	}
+
+	pub fn setEnabled(&mut self, enabled: bool) { self.enabled = enabled; }
+
+	pub fn isEnabled(&self) -> bool { self.enabled }
+
+	// A file seen for the first time is only remembered
+	fn changed(&mut self, path: &str) -> bool
+	{
+		let time = match std::fs::metadata(path).and_then(|m| m.modified())
+		{
+			Ok(x) => x,
+			Err(_) => return false
+		};
+		self.times.insert(path.to_string(), time).is_some_and(|old| old != time)
+	}
+
+	pub fn update(&mut self)
+	{
+		if !self.enabled { return; }
+		self.timer += Window::getDeltaTime();
+		if self.timer < INTERVAL { return; }
+		self.timer = 0.0;
+
+		let res = Window::getResources();
+		for path in res.getTexturePaths()
+		{
+			if self.changed(&path) && res.reloadTexture(&path)
+			{
+				println!("Reloaded texture {path}");
+			}
+		}
+
+		for name in res.getShaderNames()
+		{
+			let vert = self.changed(&format!("res/shaders/{name}.vert"));
+			let frag = self.changed(&format!("res/shaders/{name}.frag"));
+			if (vert || frag) && res.reloadShader(&name)
+			{
+				println!("Reloaded shader {name}");
+			}
+		}
+
+		// Several objects may share a script, every one of them is reloaded
+		let ui = Window::getUI();
+		let scripts = ui.getScripts();
+		let mut paths: Vec<&String> = scripts.iter().map(|(_, path)| path).collect();
+		paths.sort();
+		paths.dedup();
+		for path in paths
+		{
+			if !self.changed(path) { continue; }
+			for (obj, _) in scripts.iter().filter(|(_, x)| x == path)
+			{
+				ui.getObject(obj.clone()).reloadScript();
+			}
+		}
+
+		let path = ui.getPath();
+		if !path.is_empty() && self.changed(&path)
+		{
+			println!("Reloading UI {path}");
+			ui.load(&path);
+		}
+	}
+}
@@ -0,0 +1,559 @@
+use std::collections::HashMap;
+
+use glfw::{Action, Key, MouseButton};
+
+use crate::ae2d::{Shapes::Rectangle, Sprite::{SliceMode, Sprite}, Text::{parseColor, Text}, Window::{Event, Window}};
+
+// Background of a widget part: an image, usually nine-slice, or a flat color
+pub enum Skin
+{
+	Image(Box<Sprite>),
+	Color(glam::Vec4)
+}
+
+impl Skin
+{
+	// "res/tex/ui/button.png", "#334" or { "image": "...", "insets": [left, top, right, bottom], "mode": "tile" },
+	// insets can also come from the image JSON
+	pub fn parse(node: &json::JsonValue) -> Option<Self>
+	{
+		if let Some(s) = node.as_str()
+		{
+			return Some(match parseColor(s)
+			{
+				Some(c) => Skin::Color(c),
+				None => Skin::Image(Box::new(Sprite::image(s.to_string())))
+			});
+		}
+
+		let mut spr = Sprite::image(node["image"].as_str()?.to_string());
+		let i = &node["insets"];
+		if let Some(x) = i.as_f32() { spr.setSlice(glam::Vec4::splat(x)); }
+		else if i.is_array()
+		{
+			spr.setSlice(glam::vec4(
+				i[0].as_f32().unwrap_or(0.0),
+				i[1].as_f32().unwrap_or(0.0),
+				i[2].as_f32().unwrap_or(0.0),
+				i[3].as_f32().unwrap_or(0.0)
+			));
+		}
+		if let Some(m) = node["mode"].as_str() { spr.setSliceMode(SliceMode::from(m)); }
+		Some(Skin::Image(Box::new(spr)))
+	}
+
+	pub fn draw(&mut self, rect: glam::Vec4, scale: f32)
+	{
+		match self
+		{
+			Skin::Color(c) =>
+			{
+				let mut r = Rectangle::new();
+				r.getTransform().setPosition(glam::vec2(rect.x, rect.y));
+				r.setSize(glam::vec2(rect.z, rect.w));
+				r.setColor(*c);
+				Window::getCamera().draw(&mut r);
+			}
+			Skin::Image(spr) =>
+			{
+				let fs = spr.getFrameSize();
+				if fs.x <= 0.0 || fs.y <= 0.0 || scale <= 0.0 { return; }
+
+				let sliced = spr.isSliced();
+				if sliced { spr.setSize(glam::vec2(rect.z, rect.w) / scale); }
+				let ts = spr.getTransformable();
+				ts.setPosition(glam::vec2(rect.x, rect.y));
+				ts.setScale(if sliced { glam::Vec2::splat(scale) } else { glam::vec2(rect.z, rect.w) / fs });
+				Window::getCamera().draw(spr.as_mut());
+			}
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind
+{
+	Button,
+	Toggle,
+	Slider,
+	Dropdown,
+	List,
+	Progress
+}
+
+// Events raised to the script of the owning object
+pub enum Signal
+{
+	Toggle(bool),
+	Value(f32),
+	Select(usize, String)
+}
+
+pub struct Widget
+{
+	kind: Kind,
+	skins: HashMap<String, Skin>,
+	label: Text,
+	item: Text,
+	color: glam::Vec4,
+	options: Vec<String>,
+	base: glam::Vec2,
+	size: glam::Vec2,
+	scale: f32,
+	hovered: bool,
+	pressed: bool,
+	checked: bool,
+	value: f32,
+	min: f32,
+	max: f32,
+	step: f32,
+	selected: Option<usize>,
+	hoveredItem: Option<usize>,
+	open: bool,
+	scroll: f32,
+	itemHeight: f32
+}
+
+// Draws the clip rectangle into the stencil buffer, stencil function and operation are set up by Window::init
+fn beginClip(rect: glam::Vec4)
+{
+	unsafe
+	{
+		gl::Enable(gl::STENCIL_TEST);
+		gl::Clear(gl::STENCIL_BUFFER_BIT);
+		gl::ColorMask(gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE);
+	}
+	Skin::Color(glam::Vec4::ONE).draw(rect, 1.0);
+	unsafe
+	{
+		gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+		gl::StencilFunc(gl::EQUAL, 1, 0xFF);
+		gl::StencilMask(0x00);
+	}
+}
+
+fn endClip()
+{
+	unsafe
+	{
+		gl::StencilMask(0xFF);
+		gl::StencilFunc(gl::NOTEQUAL, 1, 0xFF);
+		gl::Disable(gl::STENCIL_TEST);
+	}
+}
+
+fn inside(r: glam::Vec4, p: glam::Vec2) -> bool
+{
+	p.x >= r.x && p.x <= r.x + r.z && p.y >= r.y && p.y <= r.y + r.w
+}
+
+impl Widget
+{
+	pub fn new(kind: Kind) -> Self
+	{
+		let mut label = Text::new();
+		let mut item = Text::new();
+		label.setSize(16.0);
+		item.setSize(16.0);
+		Self
+		{
+			kind,
+			skins: HashMap::new(),
+			label,
+			item,
+			color: glam::Vec4::ONE,
+			options: vec![],
+			base: glam::vec2(100.0, 24.0),
+			size: glam::vec2(100.0, 24.0),
+			scale: 1.0,
+			hovered: false,
+			pressed: false,
+			checked: false,
+			value: 0.0,
+			min: 0.0,
+			max: 1.0,
+			step: 0.0,
+			selected: None,
+			hoveredItem: None,
+			open: false,
+			scroll: 0.0,
+			itemHeight: 0.0
+		}
+	}
+
+	pub fn parse(node: &json::JsonValue) -> Self
+	{
+		let kind = match node["type"].as_str().unwrap_or_default()
+		{
+			"toggle" | "checkbox" => Kind::Toggle,
+			"slider" => Kind::Slider,
+			"dropdown" => Kind::Dropdown,
+			"list" => Kind::List,
+			"progress" => Kind::Progress,
+			_ => Kind::Button
+		};
+		let mut w = Self::new(kind);
+
+		for (x, y) in node.entries()
+		{
+			if x == "size"
+			{
+				w.base = glam::vec2(y[0].as_f32().unwrap_or(100.0), y[1].as_f32().unwrap_or(24.0));
+				w.size = w.base;
+			}
+			if x == "font"
+			{
+				w.label.setFont(y.as_str().unwrap().to_string());
+				w.item.setFont(y.as_str().unwrap().to_string());
+			}
+			if x == "fontSize"
+			{
+				w.label.setSize(y.as_f32().unwrap());
+				w.item.setSize(y.as_f32().unwrap());
+			}
+			if x == "color" { w.color = parseColor(y.as_str().unwrap_or_default()).unwrap_or(glam::Vec4::ONE); }
+			if x == "text" { w.label.setString(y.as_str().unwrap().to_string()); }
+			if x == "checked" { w.checked = y.as_bool().unwrap_or(false); }
+			if x == "min" { w.min = y.as_f32().unwrap(); }
+			if x == "max" { w.max = y.as_f32().unwrap(); }
+			if x == "step" { w.step = y.as_f32().unwrap(); }
+			if x == "itemHeight" { w.itemHeight = y.as_f32().unwrap(); }
+			if x == "items"
+			{
+				w.options = y.members()
+					.filter_map(|x| x.as_str())
+					.map(|x| x.to_string())
+					.collect();
+			}
+			if x == "skin"
+			{
+				for (name, value) in y.entries()
+				{
+					if let Some(s) = Skin::parse(value) { w.skins.insert(name.to_string(), s); }
+				}
+			}
+		}
+
+		w.setValue(node["value"].as_f32().unwrap_or(w.min));
+		w.selected = node["selected"].as_usize().filter(|x| *x < w.options.len());
+		w
+	}
+
+	pub fn getKind(&self) -> Kind { self.kind }
+
+	pub fn getBaseSize(&self) -> glam::Vec2 { self.base }
+
+	// Final size in pixels, given by the layout
+	pub fn setRect(&mut self, size: glam::Vec2, scale: f32)
+	{
+		self.size = size;
+		self.scale = scale;
+	}
+
+	pub fn getBounds(&self) -> glam::Vec4 { glam::vec4(0.0, 0.0, self.size.x, self.size.y) }
+
+	fn itemHeight(&self) -> f32
+	{
+		if self.itemHeight > 0.0 { return self.itemHeight * self.scale; }
+		match self.kind
+		{
+			Kind::Dropdown => self.size.y,
+			_ => self.item.getSize() * 1.5 * self.scale
+		}
+	}
+
+	// The opened list of a dropdown, drawn above every other object
+	pub fn getOverlay(&self) -> Option<glam::Vec4>
+	{
+		if self.kind != Kind::Dropdown || !self.open { return None; }
+		Some(glam::vec4(0.0, self.size.y, self.size.x, self.itemHeight() * self.options.len() as f32))
+	}
+
+	fn maxScroll(&self) -> f32
+	{
+		(self.itemHeight() * self.options.len() as f32 - self.size.y).max(0.0)
+	}
+
+	pub fn update(&mut self, mouse: glam::Vec2, hovered: bool, focused: bool) -> Vec<Signal>
+	{
+		let mut out = vec![];
+		let h = self.itemHeight();
+		self.hovered = hovered;
+		self.hoveredItem = match (self.kind, self.getOverlay())
+		{
+			(Kind::Dropdown, Some(r)) if hovered && inside(r, mouse) => Some(((mouse.y - r.y) / h) as usize),
+			(Kind::List, _) if hovered => Some(((mouse.y + self.scroll) / h) as usize),
+			_ => None
+		}.filter(|i| *i < self.options.len());
+
+		for e in Window::getInstance().eventQueue.clone()
+		{
+			match e
+			{
+				Event::Mouse(MouseButton::Button1, Action::Press, _) =>
+				{
+					self.pressed = hovered;
+					if !hovered { self.open = false; }
+				}
+				Event::Mouse(MouseButton::Button1, Action::Release, _) =>
+				{
+					if self.pressed && hovered { self.click(&mut out); }
+					self.pressed = false;
+				}
+				Event::Scroll(d) if hovered && self.kind == Kind::List =>
+				{
+					self.scroll = (self.scroll - d * h).clamp(0.0, self.maxScroll());
+				}
+				Event::Key(k, Action::Press | Action::Repeat, _) if focused => self.key(k, &mut out),
+				_ => {}
+			}
+		}
+
+		if self.kind == Kind::Slider && self.pressed && self.size.x > 0.0
+		{
+			let t = (mouse.x / self.size.x).clamp(0.0, 1.0);
+			if self.setValue(self.min + t * (self.max - self.min)) { out.push(Signal::Value(self.value)); }
+		}
+
+		out
+	}
+
+	// Called for disabled or hidden widgets so they don't stay pressed or opened
+	pub fn reset(&mut self)
+	{
+		self.hovered = false;
+		self.pressed = false;
+		self.open = false;
+		self.hoveredItem = None;
+	}
+
+	fn click(&mut self, out: &mut Vec<Signal>)
+	{
+		match self.kind
+		{
+			Kind::Toggle =>
+			{
+				self.checked = !self.checked;
+				out.push(Signal::Toggle(self.checked));
+			}
+			Kind::Dropdown =>
+			{
+				match self.hoveredItem.filter(|_| self.open)
+				{
+					Some(i) => { self.select(i, out); self.open = false; }
+					None => self.open = !self.open
+				}
+			}
+			Kind::List =>
+			{
+				if let Some(i) = self.hoveredItem { self.select(i, out); }
+			}
+			_ => {}
+		}
+	}
+
+	fn key(&mut self, key: Key, out: &mut Vec<Signal>)
+	{
+		let step = if self.step > 0.0 { self.step } else { (self.max - self.min) / 10.0 };
+		let last = self.options.len().saturating_sub(1);
+		match (self.kind, key)
+		{
+			(Kind::Toggle | Kind::Dropdown, Key::Enter | Key::KpEnter | Key::Space) => self.click(out),
+			(Kind::Slider, Key::Left | Key::Down | Key::Right | Key::Up) =>
+			{
+				let d = if matches!(key, Key::Left | Key::Down) { -step } else { step };
+				if self.setValue(self.value + d) { out.push(Signal::Value(self.value)); }
+			}
+			(Kind::Dropdown | Kind::List, Key::Up) if !self.options.is_empty() =>
+			{
+				self.select(self.selected.map(|x| x.saturating_sub(1)).unwrap_or(0), out);
+			}
+			(Kind::Dropdown | Kind::List, Key::Down) if !self.options.is_empty() =>
+			{
+				self.select(self.selected.map(|x| (x + 1).min(last)).unwrap_or(0), out);
+			}
+			(_, Key::Escape) => self.open = false,
+			_ => {}
+		}
+	}
+
+	fn select(&mut self, i: usize, out: &mut Vec<Signal>)
+	{
+		if self.selected == Some(i) { return; }
+		self.selected = Some(i);
+		out.push(Signal::Select(i, self.options[i].clone()));
+
+		// Keeps the selected item of a list visible
+		let h = self.itemHeight();
+		let y = i as f32 * h;
+		if y < self.scroll { self.scroll = y; }
+		if y + h > self.scroll + self.size.y { self.scroll = y + h - self.size.y; }
+		self.scroll = self.scroll.clamp(0.0, self.maxScroll());
+	}
+
+	// Snaps to the step and clamps to the range, returns true if the value changed
+	pub fn setValue(&mut self, v: f32) -> bool
+	{
+		let mut v = v;
+		if self.step > 0.0 { v = self.min + ((v - self.min) / self.step).round() * self.step; }
+		v = v.clamp(self.min.min(self.max), self.max.max(self.min));
+		if v == self.value { return false; }
+		self.value = v;
+		true
+	}
+
+	pub fn getValue(&self) -> f32 { self.value }
+
+	pub fn setRange(&mut self, min: f32, max: f32, step: f32)
+	{
+		self.min = min;
+		self.max = max;
+		self.step = step;
+		self.setValue(self.value);
+	}
+
+	pub fn setChecked(&mut self, checked: bool) { self.checked = checked; }
+	pub fn isChecked(&self) -> bool { self.checked }
+
+	pub fn setText(&mut self, s: String) { self.label.setString(s); }
+	pub fn getText(&self) -> String { self.label.getString() }
+
+	pub fn setItems(&mut self, items: Vec<String>)
+	{
+		self.options = items;
+		self.selected = self.selected.filter(|x| *x < self.options.len());
+		self.scroll = self.scroll.clamp(0.0, self.maxScroll());
+	}
+
+	pub fn getItems(&self) -> Vec<String> { self.options.clone() }
+
+	pub fn setSelected(&mut self, i: Option<usize>) { self.selected = i.filter(|x| *x < self.options.len()); }
+	pub fn getSelected(&self) -> Option<usize> { self.selected }
+
+	pub fn setSkin(&mut self, name: String, skin: Skin) { self.skins.insert(name, skin); }
+
+	pub fn relocalize(&mut self) { self.label.relocalize(); }
+
+	// "pressed" falls back to "hover", "hover" and "disabled" fall back to "normal"
+	fn drawSkin(&mut self, name: &str, rect: glam::Vec4)
+	{
+		let chain: &[&str] = match name
+		{
+			"pressed" => &["pressed", "hover", "normal"],
+			"hover" => &["hover", "normal"],
+			"disabled" => &["disabled", "normal"],
+			"popup" => &["popup", "normal"],
+			_ => &[name]
+		};
+		let scale = self.scale;
+		if let Some(n) = chain.iter().find(|n| self.skins.contains_key(**n))
+		{
+			self.skins.get_mut(*n).unwrap().draw(rect, scale);
+		}
+	}
+
+	// Places a text vertically centered at the given height, x < 0 centers it horizontally
+	fn drawText(text: &mut Text, x: f32, y: f32, width: f32, scale: f32, color: glam::Vec4)
+	{
+		let d = text.getDimensions() * scale;
+		let ts = text.getTransformable();
+		ts.setScale(glam::Vec2::splat(scale));
+		ts.setPosition(glam::vec2(
+			if x < 0.0 { (width - d.x) / 2.0 } else { x },
+			y - d.y / 2.0
+		));
+		text.setColor(color);
+		Window::getCamera().draw(text);
+	}
+
+	fn drawItems(&mut self, rect: glam::Vec4, scroll: f32)
+	{
+		let h = self.itemHeight();
+		if h <= 0.0 { return; }
+		let first = (scroll / h) as usize;
+		let last = (((scroll + rect.w) / h).ceil() as usize).min(self.options.len());
+		let pad = 4.0 * self.scale;
+		for i in first..last
+		{
+			let r = glam::vec4(rect.x, rect.y + i as f32 * h - scroll, rect.z, h);
+			if self.selected == Some(i) { self.drawSkin("selected", r); }
+			else if self.hoveredItem == Some(i) { self.drawSkin("item", r); }
+			self.item.setString(self.options[i].clone());
+			Widget::drawText(&mut self.item, r.x + pad, r.y + h / 2.0, r.z, self.scale, self.color);
+		}
+	}
+
+	pub fn draw(&mut self, active: bool)
+	{
+		let rect = self.getBounds();
+		let (w, h) = (self.size.x, self.size.y);
+		let pad = 4.0 * self.scale;
+		let t = if self.max != self.min { ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0) } else { 0.0 };
+		let state = if !active { "disabled" }
+			else if self.pressed || self.open { "pressed" }
+			else if self.hovered { "hover" }
+			else { "normal" };
+
+		match self.kind
+		{
+			Kind::Button =>
+			{
+				self.drawSkin(state, rect);
+				Widget::drawText(&mut self.label, -1.0, h / 2.0, w, self.scale, self.color);
+			}
+			Kind::Toggle =>
+			{
+				let b = glam::vec4(0.0, 0.0, h, h);
+				self.drawSkin(state, b);
+				if self.checked { self.drawSkin("check", b); }
+				Widget::drawText(&mut self.label, h + pad, h / 2.0, w, self.scale, self.color);
+			}
+			Kind::Slider =>
+			{
+				self.drawSkin(if active { "normal" } else { "disabled" }, rect);
+				self.drawSkin("fill", glam::vec4(0.0, 0.0, w * t, h));
+				let k = glam::vec4((w * t - h / 2.0).clamp(0.0, (w - h).max(0.0)), 0.0, h, h);
+				self.drawSkin("knob", k);
+			}
+			Kind::Progress =>
+			{
+				self.drawSkin(if active { "normal" } else { "disabled" }, rect);
+				self.drawSkin("fill", glam::vec4(0.0, 0.0, w * t, h));
+				if !self.label.getString().is_empty()
+				{
+					Widget::drawText(&mut self.label, -1.0, h / 2.0, w, self.scale, self.color);
+				}
+			}
+			Kind::Dropdown =>
+			{
+				self.drawSkin(state, rect);
+				self.drawSkin("arrow", glam::vec4(w - h, 0.0, h, h));
+				match self.selected
+				{
+					Some(i) =>
+					{
+						self.item.setString(self.options[i].clone());
+						Widget::drawText(&mut self.item, pad, h / 2.0, w, self.scale, self.color);
+					}
+					None => Widget::drawText(&mut self.label, pad, h / 2.0, w, self.scale, self.color)
+				}
+			}
+			Kind::List =>
+			{
+				self.drawSkin(if active { "normal" } else { "disabled" }, rect);
+				beginClip(rect);
+				self.drawItems(rect, self.scroll);
+				endClip();
+			}
+		}
+	}
+
+	pub fn drawOverlay(&mut self)
+	{
+		if let Some(r) = self.getOverlay()
+		{
+			self.drawSkin("popup", r);
+			self.drawItems(r, 0.0);
+		}
+	}
+}
@@ -1,10 +1,37 @@
-use std::collections::HashMap;
 use glfw::Context;
 
-use crate::ae2d::{Network::Network, Profiler::Profiler, Shader::Shader, World::World};
+use crate::ae2d::{Audio::Audio, Gamepad::Gamepad, Input::Input, Locale::Locale, Network::Network, Profiler::Profiler, Resources::{Handle, Resources, Texture}, Shader::Shader, Text::IconSet, Watcher::Watcher, World::World};
 
 use super::{Camera::Camera, Programmable::{Programmable, Variable}, UI::UI};
 
+const KEY_NAMES: [&str; 69] = [
+	"A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O",
+	"P", "Q", "R", "S", "T", "U", "V", "W", "X", "Y", "Z", "Num0", "Num1", "Num2",
+	"Num3", "Num4", "Num5", "Num6", "Num7", "Num8", "Num9", "Escape", "Enter",
+	"Backspace", "Space", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9",
+	"F10", "F11", "F12", "Left", "Right", "Up", "Down", "Home", "End", "LShift",
+	"RShift", "LCtrl", "RCtrl", "LAlt", "RAlt", "Tab", "Minus", "Equal",
+	"KpSubtract", "KpAdd"
+];
+
+// Init hints of GLFW 3.4, the glfw crate doesn't export them
+const GLFW_PLATFORM: i32 = 0x00050003;
+const GLFW_PLATFORM_NULL: i32 = 0x00060005;
+
+// Set once glfw::init has run, init hints given after that are ignored by GLFW
+static GLFW_STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+#[derive(Clone, Debug)]
+pub enum Event
+{
+	Key(glfw::Key, glfw::Action, glfw::Modifiers),
+	Mouse(glfw::MouseButton, glfw::Action, glfw::Modifiers),
+	Char(char),
+	Scroll(f32),
+	Drop(Vec<String>),
+	Resize(i32, i32)
+}
+
 pub struct Window
 {
 	context: glfw::Glfw,
@@ -12,20 +39,35 @@ pub struct Window
 	events: Option<glfw::GlfwReceiver<(f64, glfw::WindowEvent)>>,
 	deltaTime: f32,
 	lastTime: std::time::Instant,
+	fixedDT: Option<f32>,
+	frameTime: f32,
+	tickRate: f32,
+	ticking: bool,
+	alpha: f32,
+	recording: Option<(String, bool, u32)>,
 	pub prog: Programmable,
 	pub mouseEvent: Option<(glfw::MouseButton, glfw::Action, glfw::Modifiers)>,
 	pub keyEvent: Option<(glfw::Key, glfw::Action, glfw::Modifiers)>,
 	pub inputEvent: Option<char>,
 	pub scrollEvent: Option<f32>,
 	pub dndEvent: Option<Vec<String>>,
+	pub eventQueue: Vec<Event>,
 	cam: Camera,
-	textures: HashMap<String, u32>,
+	resources: Resources,
 	ui: UI,
 	net: Network,
 	world: World,
-	shaders: HashMap<String, Shader>,
 	server: Option<std::process::Child>,
-        profiler: Profiler
+        profiler: Profiler,
+	audio: Audio,
+	headless: bool,
+	framebuffer: (i32, i32, Vec<u8>),
+	screenshots: Vec<(String, bool)>,
+	input: Input,
+	gamepad: Gamepad,
+	icons: IconSet,
+	locale: Locale,
+	watcher: Watcher
 }
 
 impl Window
@@ -33,6 +75,7 @@ impl Window
 	pub fn default() -> Window
 	{
 		use glfw::fail_on_errors;
+		GLFW_STARTED.store(true, std::sync::atomic::Ordering::Relaxed);
 		Window
 		{
 			context: glfw::init(glfw::fail_on_errors!()).unwrap(),
@@ -40,20 +83,35 @@ impl Window
 			events: None,
 			deltaTime: 0.0,
 			lastTime: std::time::Instant::now(),
+			fixedDT: None,
+			frameTime: 0.0,
+			tickRate: 0.0,
+			ticking: false,
+			alpha: 1.0,
+			recording: None,
 			prog: Programmable::new(),
 			mouseEvent: None,
 			keyEvent: None,
 			cam: Camera::new(),
-			textures: HashMap::new(),
+			resources: Resources::new(),
 			ui: UI::new(),
 			net: Network::new(),
 			inputEvent: None,
 			world: World::new(),
-			shaders: HashMap::new(),
 			server: None,
 			scrollEvent: None,
 			dndEvent: None,
-                        profiler: Profiler::new()
+			eventQueue: vec![],
+                        profiler: Profiler::new(),
+			audio: Audio::new(),
+			headless: false,
+			framebuffer: (0, 0, vec![]),
+			screenshots: vec![],
+			input: Input::new(),
+			gamepad: Gamepad::new(),
+			icons: IconSet::new(),
+			locale: Locale::new(),
+			watcher: Watcher::new()
 		}
 	}
 
@@ -75,16 +133,40 @@ impl Window
 		);
 		if cfg.is_err() { return; }
 		let cfg = cfg.unwrap();
+
+		let headless = cfg["main"]["headless"].as_bool().unwrap_or(false) ||
+			std::env::var("AE2D_HEADLESS").is_ok_and(|x| x == "1" || x == "true");
+		// The null platform needs no display, it has to be chosen before the instance initializes GLFW
+		if headless
+		{
+			if GLFW_STARTED.load(std::sync::atomic::Ordering::Relaxed)
+			{
+				println!("Window::getInstance() was called before Window::init, headless mode may require a display");
+			}
+			else { unsafe { glfw::ffi::glfwInitHint(GLFW_PLATFORM, GLFW_PLATFORM_NULL); } }
+		}
 		
 		let i = Window::getInstance();
+		i.headless = headless;
 
 		i.context.window_hint(glfw::WindowHint::ContextVersion(2, 1));
+		if headless
+		{
+			i.context.window_hint(glfw::WindowHint::Visible(false));
+			// GLFW_CONTEXT_CREATION_API = GLFW_OSMESA_CONTEXT_API
+			i.context.window_hint(glfw::WindowHint::ContextCreationApi(
+				glfw::ContextCreationApi::OsMesa
+			));
+		}
 
 		let mut title = "Ae2D";
 		let mut size = glam::vec2(1280.0, 720.0);
 		let mut vsync = true;
 		let mut fullscreen = false;
 		let mut uiPath = "";
+		let mut inputPath = "res/global/input.json";
+		let mut iconsPath = "res/global/icons.json";
+		let mut language = "en";
 
 		for (name, section) in cfg.entries()
 		{
@@ -115,6 +197,30 @@ impl Window
 					{
 						uiPath = y.as_str().unwrap();
 					}
+					if x == "inputPath"
+					{
+						inputPath = y.as_str().unwrap();
+					}
+					if x == "iconsPath"
+					{
+						iconsPath = y.as_str().unwrap();
+					}
+					if x == "language"
+					{
+						language = y.as_str().unwrap();
+					}
+					if x == "langPath"
+					{
+						i.locale.setDir(y.as_str().unwrap().to_string());
+					}
+					if x == "hotReload"
+					{
+						i.watcher.setEnabled(y.as_bool().unwrap_or(false));
+					}
+					if x == "tickRate"
+					{
+						i.tickRate = y.as_f32().unwrap_or(0.0).max(0.0);
+					}
 				}
 			}
 			if name == "custom"
@@ -135,7 +241,7 @@ impl Window
 			}
 		}
 
-		if fullscreen
+		if fullscreen && !headless
 		{
 			vsync = true;
 			i.context.with_primary_monitor(|g, monitor|
@@ -157,7 +263,7 @@ impl Window
 			i.context.create_window(size.x as u32, size.y as u32,
 			title,
 			glfw::WindowMode::Windowed
-		).unwrap();
+		).expect(if headless { "Failed to create headless context" } else { "Failed to create window" });
 
 		window.set_mouse_button_polling(true);
 		window.set_key_polling(true);
@@ -178,6 +284,8 @@ impl Window
 		i.events = Some(events);
 
 		i.cam.load();
+		if headless { println!("Running in headless mode, sound is disabled."); }
+		else { i.audio.load(); }
 		
 		unsafe
 		{
@@ -192,6 +300,9 @@ impl Window
 			println!("{}", Self::getGLString(gl::RENDERER));
 		}
 
+		i.input.load(inputPath.to_string());
+		i.icons = IconSet::load(iconsPath);
+		i.locale.load(language.to_string());
 		i.ui.load(uiPath);
 	}
 
@@ -222,7 +333,13 @@ impl Window
 		i.inputEvent = None;
 		i.scrollEvent = None;
 		i.dndEvent = None;
-		i.deltaTime = i.lastTime.elapsed().as_secs_f32().min(0.1);
+		i.eventQueue.clear();
+		i.deltaTime = match i.fixedDT
+		{
+			Some(dt) => dt,
+			None => i.lastTime.elapsed().as_secs_f32().min(0.1)
+		};
+		i.frameTime = i.deltaTime;
 		i.lastTime = std::time::Instant::now();
 		
 		let events = i.events.as_ref().unwrap();
@@ -241,13 +358,16 @@ impl Window
 				glfw::WindowEvent::MouseButton(b, a, m) =>
 				{
 					i.mouseEvent = Some((b, a, m));
+					i.eventQueue.push(Event::Mouse(b, a, m));
 				}
 				glfw::WindowEvent::Key(k, _, a, m) =>
 				{
 					i.keyEvent = Some((k, a, m));
+					i.eventQueue.push(Event::Key(k, a, m));
 				}
 				glfw::WindowEvent::Size(w, h) =>
 				{
+					i.eventQueue.push(Event::Resize(w, h));
 					i.cam.setSize(false, (w, h));
 					i.ui.resize();
 					unsafe
@@ -258,10 +378,12 @@ impl Window
 				glfw::WindowEvent::Char(c) =>
 				{
 					i.inputEvent = Some(c);
+					i.eventQueue.push(Event::Char(c));
 				}
 				glfw::WindowEvent::Scroll(_, dist) =>
 				{
 					i.scrollEvent = Some(dist as f32);
+					i.eventQueue.push(Event::Scroll(dist as f32));
 				}
 				glfw::WindowEvent::FileDrop(files) =>
 				{
@@ -270,30 +392,74 @@ impl Window
 							x.to_string_lossy().to_string())
 							.collect::<Vec<String>>()
 					);
+					i.eventQueue.push(Event::Drop(i.dndEvent.clone().unwrap()));
 				}
 				e => println!("{e:?}")
 			}
 		}
 
+		i.gamepad.update(&i.context);
+		i.input.update();
+
 		i.profiler.save("winUpdate".to_string());
 
+		i.watcher.update();
 		i.ui.updateReload();
-		i.world.update();
+		if i.tickRate == 0.0 { i.world.update(); }
 		i.ui.update();
+		i.audio.update();
+		i.resources.update();
+	}
+
+	pub fn tick()
+	{
+		let i = Window::getInstance();
+		if i.tickRate == 0.0 { return; }
+		i.deltaTime = 1.0 / i.tickRate;
+		i.input.latch();
+		i.gamepad.latch();
+		i.ticking = true;
+		i.world.update();
+		i.ticking = false;
+		i.deltaTime = i.frameTime;
 	}
 
+	// True while the world is updated by tick(), lets input report edges per tick
+	pub fn isTicking() -> bool { Window::getInstance().ticking }
+
+	pub fn getTickStep() -> Option<f32>
+	{
+		let r = Window::getInstance().tickRate;
+		if r > 0.0 { Some(1.0 / r) } else { None }
+	}
+
+	pub fn getFrameTime() -> f32 { Window::getInstance().frameTime }
+
+	pub fn setAlpha(alpha: f32) { Window::getInstance().alpha = alpha.clamp(0.0, 1.0); }
+
+	pub fn getAlpha() -> f32 { Window::getInstance().alpha }
+
 	pub fn render()
 	{
 		let i = Window::getInstance();
 
 		if i.window.as_mut().unwrap().is_iconified() { return; }
 
+		if let Some((dir, worldOnly, frame)) = &mut i.recording
+		{
+			*frame += 1;
+			i.screenshots.push((format!("{dir}/{frame:06}.png"), *worldOnly));
+		}
+
 		i.cam.clear();
 		i.cam.toggleTransform(true);
 		i.cam.draw(&mut i.world);
 		i.cam.toggleTransform(false);
+		Window::processScreenshots(true);
 		i.cam.display();
 		i.cam.draw(&mut i.ui);
+		Window::processScreenshots(false);
+		if i.headless { i.framebuffer = Window::readScreen(); }
 		i.profiler.restart();
 		i.window.as_mut().unwrap().swap_buffers();
 		i.profiler.save("swap".to_string());
@@ -307,9 +473,106 @@ impl Window
 			let x = gl::GetError();
 			if x != 0 { println!("GL Error: {x}"); }
 		}
-		Window::getInstance().window.as_mut().unwrap().swap_buffers();
+		Window::processScreenshots(true);
+		Window::processScreenshots(false);
+		let i = Window::getInstance();
+		if i.headless { i.framebuffer = Window::readScreen(); }
+		i.window.as_mut().unwrap().swap_buffers();
 	}
 
+	// The size has to be the size of the framebuffer that is read
+	pub fn readPixels(fbo: u32, (w, h): (i32, i32)) -> (i32, i32, Vec<u8>)
+	{
+		if w <= 0 || h <= 0 { return (0, 0, vec![]); }
+
+		let row = w as usize * 4;
+		let mut data = vec![0u8; row * h as usize];
+		unsafe
+		{
+			gl::BindFramebuffer(gl::FRAMEBUFFER, fbo);
+			gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+			gl::ReadPixels(
+				0, 0, w, h,
+				gl::RGBA, gl::UNSIGNED_BYTE,
+				data.as_mut_ptr() as *mut _
+			);
+			gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+		}
+
+		let mut flipped = Vec::with_capacity(data.len());
+		for y in data.chunks(row).rev() { flipped.extend_from_slice(y); }
+		(w, h, flipped)
+	}
+
+	fn readScreen() -> (i32, i32, Vec<u8>)
+	{
+		let size = Window::getInstance().window.as_ref().unwrap().get_framebuffer_size();
+		Window::readPixels(0, size)
+	}
+
+	pub fn savePNG(path: &str, image: &(i32, i32, Vec<u8>)) -> bool
+	{
+		if let Some(dir) = std::path::Path::new(path).parent()
+		{
+			let _ = std::fs::create_dir_all(dir);
+		}
+
+		let file = std::fs::File::create(path);
+		if let Err(x) = file
+		{
+			println!("Failed to save image to {path}:\n{x}");
+			return false;
+		}
+
+		let mut encoder = png::Encoder::new(
+			std::io::BufWriter::new(file.unwrap()),
+			image.0 as u32, image.1 as u32
+		);
+		encoder.set_color(png::ColorType::Rgba);
+		encoder.set_depth(png::BitDepth::Eight);
+
+		let res = encoder.write_header()
+			.and_then(|mut w| w.write_image_data(&image.2));
+		if let Err(x) = res
+		{
+			println!("Failed to save image to {path}:\n{x}");
+			return false;
+		}
+		true
+	}
+
+	pub fn screenshot(path: String, worldOnly: bool)
+	{
+		Window::getInstance().screenshots.push((path, worldOnly));
+	}
+
+	fn processScreenshots(worldOnly: bool)
+	{
+		let i = Window::getInstance();
+		if i.screenshots.is_empty() { return; }
+
+		let mut image = None;
+		i.screenshots.retain(|(path, w)|
+		{
+			if *w != worldOnly { return true; }
+			let img = image.get_or_insert_with(||
+			{
+				let mut x = if worldOnly { i.cam.capture() } else { Window::readScreen() };
+				for p in x.2.chunks_mut(4) { p[3] = 255; }
+				x
+			});
+			Window::savePNG(path, img);
+			false
+		});
+	}
+
+	pub fn getFramebuffer() -> &'static (i32, i32, Vec<u8>)
+	{
+		&Window::getInstance().framebuffer
+	}
+
+	pub fn isHeadless() -> bool { Window::getInstance().headless }
+
 	pub fn getSize() -> (i32, i32)
 	{
 		Window::getInstance().window.as_ref().unwrap().get_size()
@@ -342,6 +605,38 @@ impl Window
 		Window::getInstance().lastTime = std::time::Instant::now();
 	}
 
+	pub fn setFixedDT(dt: Option<f32>)
+	{
+		Window::getInstance().fixedDT = dt.filter(|x| *x > 0.0);
+	}
+
+	pub fn getFixedDT() -> Option<f32> { Window::getInstance().fixedDT }
+
+	pub fn startRecording(dir: String, fps: f32, worldOnly: bool)
+	{
+		let i = Window::getInstance();
+		if let Err(x) = std::fs::create_dir_all(&dir)
+		{
+			println!("Failed to start recording to {dir}:\n{x}");
+			return;
+		}
+		println!("Recording frames to {dir} at {fps} FPS");
+		i.fixedDT = Some(1.0 / fps.max(1.0));
+		i.recording = Some((dir, worldOnly, 0));
+	}
+
+	pub fn stopRecording()
+	{
+		let i = Window::getInstance();
+		if let Some((dir, _, frame)) = i.recording.take()
+		{
+			println!("Recorded {frame} frames to {dir}");
+			i.fixedDT = None;
+		}
+	}
+
+	pub fn isRecording() -> bool { Window::getInstance().recording.is_some() }
+
 	pub fn strToMB(name: String) -> glfw::MouseButton
 	{
 		match name.as_str()
@@ -353,6 +648,26 @@ impl Window
 		}
 	}
 
+	pub fn mbToStr(button: glfw::MouseButton) -> String
+	{
+		match button
+		{
+			glfw::MouseButton::Button1 => "Left",
+			glfw::MouseButton::Button2 => "Right",
+			glfw::MouseButton::Button3 => "Middle",
+			_ => ""
+		}.to_string()
+	}
+
+	pub fn keyToStr(key: glfw::Key) -> String
+	{
+		for name in KEY_NAMES
+		{
+			if Window::strToKey(name.to_string()) == key { return name.to_string(); }
+		}
+		String::new()
+	}
+
 	pub fn strToKey(name: String) -> glfw::Key
 	{
 		match name.as_str()
@@ -430,6 +745,16 @@ impl Window
 		}
 	}
 
+	pub fn actionToStr(action: glfw::Action) -> String
+	{
+		match action
+		{
+			glfw::Action::Press => "press",
+			glfw::Action::Release => "release",
+			glfw::Action::Repeat => "repeat"
+		}.to_string()
+	}
+
 	pub fn strToMod(name: String) -> glfw::Modifiers
 	{
 		match name.as_str()
@@ -443,68 +768,19 @@ impl Window
 		}
 	}
 
-	pub fn getTexture(path: String) -> u32
+	pub fn getTexture(path: String) -> Handle<Texture>
 	{
-		let tex = &mut Window::getInstance().textures;
-		if let Some(t) = tex.get(&path) { return *t; }
-		
-		match stb_image::image::load(path.clone())
-		{
-			stb_image::image::LoadResult::ImageU8(data) =>
-			{
-				let mut t = 0;
-				unsafe
-				{
-					gl::GenTextures(1, &mut t);
-					gl::BindTexture(gl::TEXTURE_2D, t);
-
-					gl::TexImage2D(
-						gl::TEXTURE_2D,
-						0,
-						gl::RGBA as i32,
-						data.width as i32,
-						data.height as i32,
-						0,
-						gl::RGBA,
-						gl::UNSIGNED_BYTE,
-						data.data.as_ptr() as *const _
-					);
-					
-					gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
-					gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
-					gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
-					gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
-				}
-
-				tex.insert(path, t);
-				t
-			},
-			stb_image::image::LoadResult::ImageF32(_) =>
-			{
-				println!("Failed to load texture from {path}: unable to read F32 type.");
-				0
-			}
-			stb_image::image::LoadResult::Error(s) =>
-			{
-				println!("Error on reading texture from {path}:\n{s}");
-				0
-			}
-		}
+		Window::getInstance().resources.texture(path)
 	}
 
 	pub fn getShader(name: String) -> &'static Shader
 	{
-		if let Some(s) = Window::getInstance().shaders.get(&name)
-		{
-			return s;
-		}
-
-		Window::getInstance().shaders.insert(name.clone(), Shader::load(
-			&(String::from("res/shaders/") + &name + ".vert"),
-			&(String::from("res/shaders/") + &name + ".frag")
-		));
+		Window::getInstance().resources.shader(name)
+	}
 
-		Window::getInstance().shaders.get(&name).unwrap()
+	pub fn getResources() -> &'static mut Resources
+	{
+		&mut Window::getInstance().resources
 	}
 
 	pub fn getNetwork() -> &'static mut Network
@@ -517,16 +793,52 @@ impl Window
 		&mut Window::getInstance().world
 	}
 
+	pub fn getInput() -> &'static mut Input
+	{
+		&mut Window::getInstance().input
+	}
+
+	pub fn getGamepad() -> &'static mut Gamepad
+	{
+		&mut Window::getInstance().gamepad
+	}
+
+	pub fn getLocale() -> &'static mut Locale
+	{
+		&mut Window::getInstance().locale
+	}
+
+	pub fn setLanguage(code: String) -> bool
+	{
+		if !Window::getLocale().load(code.clone())
+		{
+			println!("Language '{code}' not found");
+			return false;
+		}
+		Window::getUI().relocalize();
+		true
+	}
+
+	pub fn getIcons() -> &'static IconSet
+	{
+		&Window::getInstance().icons
+	}
+
+	pub fn getAudio() -> &'static mut Audio
+	{
+		&mut Window::getInstance().audio
+	}
+
 	pub fn clearCache()
 	{
 		let i = Window::getInstance();
-		i.textures.clear();
-		i.shaders.clear();
+		i.resources.clearCache();
+		i.audio.clearCache();
 	}
 
 	pub fn updateMatrices(proj: glam::Mat4, view: glam::Mat4)
 	{
-		for (_, s) in &mut Window::getInstance().shaders
+		for s in Window::getInstance().resources.getShaders()
 		{
 			s.activate();
 			s.setMat4("projection", proj);
@@ -57,6 +57,11 @@ impl World
 		bind::window(&self.script);
 		bind::network(&self.script);
 		bind::world(&self.script);
+		bind::audio(&self.script);
+		bind::input(&self.script);
+		bind::gamepad(&self.script);
+		bind::loc(&self.script);
+		bind::resources(&self.script);
 	}
 
 	pub fn update(&mut self)
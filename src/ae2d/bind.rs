@@ -2,41 +2,144 @@ use std::{io::Write, net::{TcpStream, UdpSocket}};
 
 use mlua::{Lua, Table};
 
-use crate::{ae2d::{Entity::Entity, Network::{Network, PlayerState}, Programmable::Variable, Transformable::Transformable2D, World::World}, server::{State::Account, Transmission::ClientMessage}};
+use crate::{ae2d::{Entity::Entity, Skeleton::Interpolation, Input::Input, Network::{Network, PlayerState}, Programmable::Variable, Transformable::Transformable2D, World::World}, server::{State::Account, Transmission::ClientMessage}};
 
-use super::{Sprite::Sprite, Text::Text, Window::Window};
+use super::{Layout::{Layout, Length}, Sprite::{SliceMode, Sprite}, UI::Object, Text::{Align, Text}, TextField::TextField, Tween::{Part, Prop, Step}, Widget::{Skin, Widget}, Window::{Event, Window}};
 
-fn getScript(id: String) -> &'static mlua::Lua
+// A mistyped path is reported to the script instead of stopping the engine
+fn findObject(path: &str) -> mlua::Result<&'static mut Object>
 {
-	let mut id = id.split("_");
-	match id.nth(0).unwrap()
+	Window::getUI().findObject(path)
+		.ok_or(mlua::Error::runtime(format!("UI object '{path}' not found")))
+}
+
+fn getScript(id: String) -> mlua::Result<&'static mlua::Lua>
+{
+	match id.split_once('_')
 	{
-		"ui" => Window::getUI().getObject(id.nth(0).unwrap().to_string()).getScript(),
-		"ent" => Window::getWorld().getEntity(id.nth(0).unwrap().to_string()).getScript(),
-		x => panic!("Script Lua: {x} not defined")
+		Some(("ui", path)) => Ok(findObject(path)?.getScript()),
+		Some(("ent", name)) => Ok(Window::getWorld().getEntity(name.to_string()).getScript()),
+		_ => Err(mlua::Error::runtime(format!("Script Lua: {id} not defined")))
 	}
 }
 
-fn getSprite(id: String) -> &'static mut Sprite
+fn getSprite(id: String) -> mlua::Result<&'static mut Sprite>
 {
-	let mut id = id.split("_");
+	match id.split_once('_')
+	{
+		Some(("ui", path)) => Ok(findObject(path)?.getSprite()),
+		Some(("ent", name)) => Ok(Window::getWorld().getEntity(name.to_string()).getSprite()),
+		_ => Err(mlua::Error::runtime(format!("Sprite Lua: {id} not defined")))
+	}
+}
 
-	match id.nth(0).unwrap()
+fn getText(id: String) -> mlua::Result<&'static mut Text>
+{
+	match id.split_once('_')
 	{
-		"ui" => Window::getUI().getObject(id.nth(0).unwrap().to_string()).getSprite(),
-		"ent" => Window::getWorld().getEntity(id.nth(0).unwrap().to_string()).getSprite(),
-		x => panic!("Sprite Lua: {x} not defined")
+		Some(("ui", path)) => Ok(findObject(path)?.getText()),
+		_ => Err(mlua::Error::runtime(format!("Text Lua: {id} not defined")))
 	}
 }
 
-fn getText(id: String) -> &'static mut Text
+fn getTextField(s: &Lua) -> mlua::Result<&'static mut TextField>
 {
-	let mut id = id.split("_");
-	
-	match id.nth(0).unwrap()
+	let id: String = s.globals().raw_get("ScriptID").unwrap();
+	let name = id.strip_prefix("ui_").unwrap_or(&id);
+	findObject(name)?.getTextField()
+		.ok_or(mlua::Error::runtime(format!("UI object '{name}' has no textField")))
+}
+
+fn getWidget(s: &Lua) -> mlua::Result<&'static mut Widget>
+{
+	let id: String = s.globals().raw_get("ScriptID").unwrap();
+	let name = id.strip_prefix("ui_").unwrap_or(&id);
+	findObject(name)?.getWidget()
+		.ok_or(mlua::Error::runtime(format!("UI object '{name}' has no widget")))
+}
+
+fn getNode(s: &Lua) -> mlua::Result<&'static mut Object>
+{
+	let id: String = s.globals().raw_get("ScriptID").unwrap();
+	findObject(id.strip_prefix("ui_").unwrap_or(&id))
+}
+
+fn getLayout(s: &Lua) -> mlua::Result<&'static mut Layout>
+{
+	let id: String = s.globals().raw_get("ScriptID").unwrap();
+	let name = id.strip_prefix("ui_").unwrap_or(&id);
+	findObject(name)?.getLayout()
+		.ok_or(mlua::Error::runtime(format!("UI object '{name}' has no layout")))
+}
+
+// "node", "sprite" and "text" are parts of the calling object, other objects are given
+// by their path with an optional part: "inventory/slot3:sprite"
+fn tweenTarget(s: &Lua, target: &str) -> (String, Part)
+{
+	let id: String = s.globals().raw_get("ScriptID").unwrap();
+	let own = id.strip_prefix("ui_").unwrap_or(&id).to_string();
+	match target
+	{
+		"node" | "sprite" | "text" => (own, Part::from(target)),
+		_ => match target.rsplit_once(':')
+		{
+			Some((path, part)) => (path.to_string(), Part::from(part)),
+			None => (target.to_string(), Part::Node)
+		}
+	}
+}
+
+fn tweenStep(s: &Lua, target: String, props: Table, duration: f32, easing: Option<String>) -> mlua::Result<Step>
+{
+	let (path, part) = tweenTarget(s, &target);
+	let laidOut = Window::getUI().findObject(&path).is_some_and(|o| o.getLayout().is_some());
+	let mut list = vec![];
+	let mut delay = 0.0;
+	for pair in props.pairs::<String, f32>()
+	{
+		let (name, value) = pair?;
+		if name == "delay" { delay = value; continue; }
+		let props = match Prop::parse(&name)
+		{
+			Some(p) => p,
+			None => return Err(mlua::Error::runtime(format!("Unknown tween property '{name}'")))
+		};
+		if laidOut && props.iter().any(|p| p.isLaidOut(part))
+		{
+			return Err(mlua::Error::runtime(format!("Tween property '{name}' of '{target}' is set by the layout")));
+		}
+		list.extend(props.into_iter().map(|p| (p, value)));
+	}
+	let name = easing.unwrap_or(String::from("Linear"));
+	let easing = Interpolation::from(name.as_str());
+	if easing.to_string() != name
+	{
+		return Err(mlua::Error::runtime(format!("Unknown easing '{name}'")));
+	}
+	let mut step = Step::new(path, part, list, duration, easing);
+	step.setDelay(delay);
+	Ok(step)
+}
+
+fn lengthFromLua(v: mlua::Value) -> Length
+{
+	match v
+	{
+		mlua::Value::Integer(x) => Length::Pixels(x as f32),
+		mlua::Value::Number(x) => Length::Pixels(x as f32),
+		mlua::Value::String(x) => Length::fromStr(&x.to_string_lossy()),
+		_ => Length::Auto
+	}
+}
+
+// A single path or a table of them
+fn pathsFromLua(v: mlua::Value) -> Vec<String>
+{
+	match v
 	{
-		"ui" => Window::getUI().getObject(id.nth(0).unwrap().to_string()).getText(),
-		x => panic!("Text Lua: {x} not defined")
+		mlua::Value::String(x) => vec![x.to_string_lossy()],
+		mlua::Value::Table(t) => t.sequence_values::<String>().flatten().collect(),
+		_ => vec![]
 	}
 }
 
@@ -67,7 +170,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("draw",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		Window::getCamera().draw(spr);
 		Ok(())
 	}).unwrap());
@@ -75,7 +178,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("size",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let s = spr.getFrameSize();
 		Ok((s.x, s.y))
 	}).unwrap());
@@ -83,7 +186,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("texSize",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let s = spr.getTexSize();
 		Ok((s.x, s.y))
 	}).unwrap());
@@ -91,7 +194,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("bounds",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let s = spr.getBounds();
 		Ok((s.x, s.y, s.z, s.w))
 	}).unwrap());
@@ -99,7 +202,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("setTextureRect",
 	s.create_function(|s, x: (f32, f32, f32, f32)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.setTextureRect(glam::vec4(x.0, x.1, x.2, x.3));
 		Ok(())
 	}).unwrap());
@@ -107,7 +210,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("setAnimation",
 	s.create_function(|s, x: String|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.setAnimation(x);
 		Ok(())
 	}).unwrap());
@@ -115,21 +218,21 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("loadAnimation",
 	s.create_function(|s, x: String|
 	{
-		*getSprite(s.globals().raw_get("ScriptID").unwrap()) = Sprite::animated(x);
+		*getSprite(s.globals().raw_get("ScriptID").unwrap())? = Sprite::animated(x);
 		Ok(())
 	}).unwrap());
 
 	let _ = t.set("loadImage",
 	s.create_function(|s, x: String|
 	{
-		*getSprite(s.globals().raw_get("ScriptID").unwrap()) = Sprite::image(x);
+		*getSprite(s.globals().raw_get("ScriptID").unwrap())? = Sprite::image(x);
 		Ok(())
 	}).unwrap());
 
 	let _ = t.set("setColor",
 	s.create_function(|s, x: (u8, u8, u8, u8)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.setColor(x);
 		Ok(())
 	}).unwrap());
@@ -137,7 +240,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("setPosition",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().setPosition(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -145,7 +248,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("translate",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().translate(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -153,7 +256,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("getPosition",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = spr.getTransformable().getPosition();
 		Ok((x.x, x.y))
 	}).unwrap());
@@ -161,7 +264,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("setOrigin",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().setOrigin(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -169,7 +272,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("getOrigin",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = spr.getTransformable().getOrigin();
 		Ok((x.x, x.y))
 	}).unwrap());
@@ -177,7 +280,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("setScale",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().setScale(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -185,7 +288,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("scale",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().scale(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -193,7 +296,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("getScale",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = spr.getTransformable().getScale();
 		Ok((x.x, x.y))
 	}).unwrap());
@@ -201,7 +304,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("setRotation",
 	s.create_function(|s, x: f32|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().setRotation(x);
 		Ok(())
 	}).unwrap());
@@ -209,7 +312,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("rotate",
 	s.create_function(|s, x: f32|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.getTransformable().rotate(x);
 		Ok(())
 	}).unwrap());
@@ -217,7 +320,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("getRotation",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = spr.getTransformable().getRotation();
 		Ok(x)
 	}).unwrap());
@@ -225,16 +328,16 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("applyModel",
 	s.create_function(|s, shader: String|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let s = Window::getCamera().activateShader(shader);
-		s.setMat4("model", spr.getTransformable().getMatrix());
+		s.setMat4("model", Window::getCamera().getParent() * spr.getTransformable().getMatrix());
 		Ok(())
 	}).unwrap());
 
 	let _ = t.set("getCurrentFrame",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		let f = spr.getCurrentFrame();
 		Ok((f.x, f.y, f.z, f.w))
 	}).unwrap());
@@ -242,7 +345,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("bindTexture",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		unsafe
 		{
 			gl::BindTexture(gl::TEXTURE_2D, spr.getTexture());
@@ -253,7 +356,7 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("tickAnimation",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.update();
 		Ok(())
 	}).unwrap());
@@ -261,11 +364,51 @@ pub fn sprite(s: &Lua)
 	let _ = t.set("resetAnimation",
 	s.create_function(|s, _: ()|
 	{
-		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap());
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
 		spr.restart();
 		Ok(())
 	}).unwrap());
 
+	let _ = t.set("setSlice",
+	s.create_function(|s, x: (f32, Option<f32>, Option<f32>, Option<f32>)|
+	{
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
+		spr.setSlice(glam::vec4(x.0, x.1.unwrap_or(x.0), x.2.unwrap_or(x.0), x.3.unwrap_or(x.0)));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("getSlice",
+	s.create_function(|s, _: ()|
+	{
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
+		let x = spr.getSlice();
+		Ok((x.x, x.y, x.z, x.w))
+	}).unwrap());
+
+	let _ = t.set("setSliceMode",
+	s.create_function(|s, x: String|
+	{
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
+		spr.setSliceMode(SliceMode::from(x.as_str()));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setSize",
+	s.create_function(|s, x: (f32, f32)|
+	{
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
+		spr.setSize(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("getSize",
+	s.create_function(|s, _: ()|
+	{
+		let spr = getSprite(s.globals().raw_get("ScriptID").unwrap())?;
+		let x = spr.getSize();
+		Ok((x.x, x.y))
+	}).unwrap());
+
 	let _ = s.globals().set("sprite", t);
 }
 
@@ -276,7 +419,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("draw",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		Window::getCamera().draw(txt);
 		Ok(())
 	}).unwrap());
@@ -284,7 +427,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("size",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let d = txt.getDimensions();
 		Ok((d.x, d.y))
 	}).unwrap());
@@ -292,7 +435,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("bounds",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let d = txt.getBounds();
 		Ok((d.x, d.y, d.z, d.w))
 	}).unwrap());
@@ -300,7 +443,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("setString",
 	s.create_function(|s, x: String|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.setString(x);
 		Ok(())
 	}).unwrap());
@@ -308,14 +451,70 @@ pub fn text(s: &Lua)
 	let _ = t.set("getString",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		Ok(txt.getString())
 	}).unwrap());
 
+	let _ = t.set("setMaxWidth",
+	s.create_function(|s, x: f32|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setMaxWidth(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setAlign",
+	s.create_function(|s, x: String|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setAlign(Align::from(x.as_str()));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setLineSpacing",
+	s.create_function(|s, x: f32|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setLineSpacing(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setMaxLines",
+	s.create_function(|s, x: usize|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setMaxLines(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setEllipsis",
+	s.create_function(|s, x: bool|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setEllipsis(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setMarkup",
+	s.create_function(|s, x: bool|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setMarkup(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.set("setLocalized",
+	s.create_function(|s, x: bool|
+	{
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
+		txt.setLocalized(x);
+		Ok(())
+	}).unwrap());
+
 	let _ = t.set("setColor",
 	s.create_function(|s, x: (u8, u8, u8, u8)|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.setColor(glam::vec4(
 			x.0 as f32 / 255.0,
 			x.1 as f32 / 255.0,
@@ -328,7 +527,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("getColor",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let c = txt.getColor();
 		Ok((c.x, c.y, c.z, c.w))
 	}).unwrap());
@@ -336,7 +535,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("setPosition",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().setPosition(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -344,7 +543,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("translate",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().translate(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -352,7 +551,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("getPosition",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = txt.getTransformable().getPosition();
 		Ok((x.x, x.y))
 	}).unwrap());
@@ -360,7 +559,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("setOrigin",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().setOrigin(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -368,7 +567,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("getOrigin",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = txt.getTransformable().getOrigin();
 		Ok((x.x, x.y))
 	}).unwrap());
@@ -376,7 +575,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("setScale",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().setScale(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -384,7 +583,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("scale",
 	s.create_function(|s, x: (f32, f32)|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().scale(glam::vec2(x.0, x.1));
 		Ok(())
 	}).unwrap());
@@ -392,7 +591,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("getScale",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = txt.getTransformable().getScale();
 		Ok((x.x, x.y))
 	}).unwrap());
@@ -400,7 +599,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("setRotation",
 	s.create_function(|s, x: f32|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().setRotation(x);
 		Ok(())
 	}).unwrap());
@@ -408,7 +607,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("rotate",
 	s.create_function(|s, x: f32|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		txt.getTransformable().rotate(x);
 		Ok(())
 	}).unwrap());
@@ -416,7 +615,7 @@ pub fn text(s: &Lua)
 	let _ = t.set("getRotation",
 	s.create_function(|s, _: ()|
 	{
-		let txt = getText(s.globals().raw_get("ScriptID").unwrap());
+		let txt = getText(s.globals().raw_get("ScriptID").unwrap())?;
 		let x = txt.getTransformable().getRotation();
 		Ok(x)
 	}).unwrap());
@@ -867,6 +1066,19 @@ pub fn window(script: &Lua)
 	{
 		Ok(Window::getSize())
 	}).unwrap());
+
+	let _ = table.raw_set("screenshot",
+	script.create_function(|_, x: (String, Option<bool>)|
+	{
+		Window::screenshot(x.0, x.1.unwrap_or(false));
+		Ok(())
+	}).unwrap());
+
+	let _ = table.raw_set("isHeadless",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::isHeadless())
+	}).unwrap());
 	
 	let _ = table.raw_set("clearCache",
 	script.create_function(|_, _: ()|
@@ -888,6 +1100,45 @@ pub fn window(script: &Lua)
 		Ok(Window::getDeltaTime())
 	}).unwrap());
 
+	let _ = table.raw_set("alpha",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getAlpha())
+	}).unwrap());
+
+	let _ = table.raw_set("tickRate",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getTickStep().map(|x| 1.0 / x).unwrap_or(0.0))
+	}).unwrap());
+
+	let _ = table.raw_set("setFixedDT",
+	script.create_function(|_, dt: Option<f32>|
+	{
+		Window::setFixedDT(dt);
+		Ok(())
+	}).unwrap());
+
+	let _ = table.raw_set("startRecording",
+	script.create_function(|_, x: (String, Option<f32>, Option<bool>)|
+	{
+		Window::startRecording(x.0, x.1.unwrap_or(60.0), x.2.unwrap_or(false));
+		Ok(())
+	}).unwrap());
+
+	let _ = table.raw_set("stopRecording",
+	script.create_function(|_, _: ()|
+	{
+		Window::stopRecording();
+		Ok(())
+	}).unwrap());
+
+	let _ = table.raw_set("isRecording",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::isRecording())
+	}).unwrap());
+
 	let _ = table.raw_set("getNum",
 	script.create_function(|_, name: String|
 	{
@@ -945,10 +1196,10 @@ pub fn window(script: &Lua)
 	let _ = table.raw_set("mouseJustPressed",
 	script.create_function(|_, name: String|
 	{
-		let e = Window::getInstance().mouseEvent;
-		if e.is_none() { return Ok(false); }
-		let e = e.unwrap();
-		Ok(e.0 == Window::strToMB(name) && e.1 == glfw::Action::Press)
+		let b = Window::strToMB(name);
+		Ok(Window::getInstance().eventQueue.iter().any(|e|
+			matches!(e, Event::Mouse(x, glfw::Action::Press, _) if *x == b)
+		))
 	}).unwrap());
 	
 	let _ = table.raw_set("keyPressed",
@@ -961,21 +1212,20 @@ pub fn window(script: &Lua)
 	let _ = table.raw_set("keyJustPressed",
 	script.create_function(|_, name: String|
 	{
-		let e = Window::getInstance().keyEvent;
-		if e.is_none() { return Ok(false); }
-		let e = e.unwrap();
-		Ok(
-			e.0 == Window::strToKey(name) &&
-			(e.1 == glfw::Action::Press || e.1 == glfw::Action::Repeat)
-		)
+		let k = Window::strToKey(name);
+		Ok(Window::getInstance().eventQueue.iter().any(|e|
+			matches!(e, Event::Key(x, glfw::Action::Press | glfw::Action::Repeat, _) if *x == k)
+		))
 	}).unwrap());
 	
 	let _ = table.raw_set("keyModPressed",
 	script.create_function(|_, name: String|
 	{
-		let e = Window::getInstance().keyEvent;
-		if e.is_none() { return Ok(false); }
-		Ok(e.unwrap().2.intersects(Window::strToMod(name)))
+		// Only the last key event of the frame counts, as before the event queue
+		let m = Window::strToMod(name);
+		Ok(Window::getInstance().eventQueue.iter().rev()
+			.find_map(|e| if let Event::Key(_, _, x) = e { Some(x.intersects(m)) } else { None })
+			.unwrap_or(false))
 	}).unwrap());
 
 	let _ = table.raw_set("close",
@@ -987,7 +1237,7 @@ pub fn window(script: &Lua)
 	let _ = table.raw_set("execute",
 	script.create_function(|_, code: (String, String)|
 	{
-		if let Err(x) = getScript(code.0.clone())
+		if let Err(x) = getScript(code.0.clone())?
 			.load(code.1)
 			.exec()
 		{
@@ -1013,9 +1263,9 @@ pub fn window(script: &Lua)
 	let _ = table.raw_set("input",
 	script.create_function(|_, _: ()|
 	{
-		let x = Window::getInstance().inputEvent;
-		if let Some(c) = x { Ok(c.to_string()) }
-		else { Ok(String::new()) }
+		Ok(Window::getInstance().eventQueue.iter().filter_map(|e|
+			if let Event::Char(c) = e { Some(*c) } else { None }
+		).collect::<String>())
 	}).unwrap());
 
 	let _ = table.raw_set("clipboard",
@@ -1035,23 +1285,15 @@ pub fn window(script: &Lua)
 	let _ = table.raw_set("screenToWorld",
 	script.create_function(|_, x: (f32, f32)|
 	{
-		let a = Window::getCamera().getTransformable().getPosition();
-		let s1 = Window::getCamera().getSize();
-		let s2 = Window::getSize();
-		let s3 = glam::vec2(s1.x / s2.0 as f32, s1.y / s2.1 as f32);
-		let p = -a - s1 / 2.0 + glam::vec2(x.0 * s3.x, x.1 * s3.y);
+		let p = Window::getCamera().screenToWorld(glam::vec2(x.0, x.1));
 		Ok((p.x, p.y))
 	}).unwrap());
 
 	let _ = table.raw_set("worldToScreen",
 	script.create_function(|_, x: (f32, f32)|
 	{
-		let t = Window::getCamera().getTransformable();
-		let p = t.getMatrix().transform_point3(glam::vec3(x.0, x.1, 0.0));
-		let s1 = Window::getCamera().getSize();
-		let s2 = Window::getSize();
-		let s3 = glam::vec2(s2.0 as f32 / s1.x, s2.1 as f32 / s1.y);
-		Ok((p.x * s3.x, p.y * s3.y))
+		let p = Window::getCamera().worldToScreen(glam::vec2(x.0, x.1));
+		Ok((p.x, p.y))
 	}).unwrap());
 
 	let _ = table.raw_set("droppedFiles",
@@ -1068,8 +1310,71 @@ pub fn window(script: &Lua)
 	let _ = table.raw_set("mouseWheel",
 	script.create_function(|_, _: ()|
 	{
-		if let Some(f) = &Window::getInstance().scrollEvent { Ok(*f) }
-		else { Ok(0.0) }
+		Ok(Window::getInstance().eventQueue.iter().map(|e|
+			if let Event::Scroll(x) = e { *x } else { 0.0 }
+		).sum::<f32>())
+	}).unwrap());
+
+	let _ = table.raw_set("events",
+	script.create_function(|s, _: ()|
+	{
+		let queue = Window::getInstance().eventQueue.clone();
+		let mut id = 0;
+		s.create_function_mut(move |s, _: ()|
+		{
+			let e = match queue.get(id) { Some(e) => e, None => return Ok(mlua::Value::Nil) };
+			id += 1;
+
+			let t = s.create_table()?;
+			let mods = |m: &glfw::Modifiers| -> mlua::Result<Table>
+			{
+				let x = s.create_table()?;
+				for name in ["Control", "Shift", "Alt", "Super", "CapsLock", "NumLock"]
+				{
+					x.raw_set(name, m.contains(Window::strToMod(name.to_string())))?;
+				}
+				Ok(x)
+			};
+			match e
+			{
+				Event::Key(k, a, m) =>
+				{
+					t.raw_set("type", "key")?;
+					t.raw_set("key", Window::keyToStr(*k))?;
+					t.raw_set("action", Window::actionToStr(*a))?;
+					t.raw_set("mods", mods(m)?)?;
+				}
+				Event::Mouse(b, a, m) =>
+				{
+					t.raw_set("type", "mouse")?;
+					t.raw_set("key", Window::mbToStr(*b))?;
+					t.raw_set("action", Window::actionToStr(*a))?;
+					t.raw_set("mods", mods(m)?)?;
+				}
+				Event::Char(c) =>
+				{
+					t.raw_set("type", "char")?;
+					t.raw_set("text", c.to_string())?;
+				}
+				Event::Scroll(x) =>
+				{
+					t.raw_set("type", "scroll")?;
+					t.raw_set("delta", *x)?;
+				}
+				Event::Drop(files) =>
+				{
+					t.raw_set("type", "drop")?;
+					t.raw_set("files", files.clone())?;
+				}
+				Event::Resize(w, h) =>
+				{
+					t.raw_set("type", "resize")?;
+					t.raw_set("width", *w)?;
+					t.raw_set("height", *h)?;
+				}
+			}
+			Ok(mlua::Value::Table(t))
+		})
 	}).unwrap());
 
 	let _ = script.globals().raw_set("window", table);
@@ -1270,12 +1575,12 @@ pub fn shapes(script: &Lua)
 		let mut ts = Transformable2D::new();
 		ts.setPosition(glam::vec2(x.0, x.1));
 		s.setVec2("size", glam::vec2(x.2, x.3));
-		s.setMat4("model", ts.getMatrix());
+		s.setMat4("model", Window::getCamera().getParent() * ts.getMatrix());
 		s.setVec4("clr", glam::vec4(
 			x.4 as f32 / 255.0,
 			x.5 as f32 / 255.0,
 			x.6 as f32 / 255.0,
-			x.7 as f32 / 255.0
+			x.7 as f32 / 255.0 * Window::getCamera().getOpacity()
 		));
 		Window::getCamera().universalVAO();
 		unsafe { gl::DrawArrays(gl::QUADS, 0, 4); }
@@ -1441,3 +1746,860 @@ pub fn profiler(script: &Lua)
     }).unwrap());
     let _ = script.globals().set("profiler", t);
 }
+
+pub fn audio(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("play",
+	script.create_function(|_, path: String|
+	{
+		Ok(Window::getAudio().play(path))
+	}).unwrap());
+
+	let _ = t.raw_set("stop",
+	script.create_function(|_, id: u32|
+	{
+		Window::getAudio().stop(id);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("stopAll",
+	script.create_function(|_, _: ()|
+	{
+		Window::getAudio().stopAll();
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isPlaying",
+	script.create_function(|_, id: u32|
+	{
+		Ok(Window::getAudio().isPlaying(id))
+	}).unwrap());
+
+	let _ = t.raw_set("setVolume",
+	script.create_function(|_, x: (u32, f32)|
+	{
+		Window::getAudio().modify(x.0, |v| v.volume = x.1.max(0.0));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setPitch",
+	script.create_function(|_, x: (u32, f32)|
+	{
+		Window::getAudio().modify(x.0, |v| v.pitch = x.1.max(0.0));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setLooping",
+	script.create_function(|_, x: (u32, bool)|
+	{
+		Window::getAudio().modify(x.0, |v| v.looping = x.1);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("pause",
+	script.create_function(|_, id: u32|
+	{
+		Window::getAudio().modify(id, |v| v.paused = true);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("resume",
+	script.create_function(|_, id: u32|
+	{
+		Window::getAudio().modify(id, |v| v.paused = false);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("playAt",
+	script.create_function(|_, x: (String, f32, f32)|
+	{
+		Ok(Window::getAudio().playAt(x.0, glam::vec2(x.1, x.2)))
+	}).unwrap());
+
+	let _ = t.raw_set("setPosition",
+	script.create_function(|_, x: (u32, f32, f32)|
+	{
+		Window::getAudio().modify(x.0, |v| v.position = Some(glam::vec2(x.1, x.2)));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setPan",
+	script.create_function(|_, x: (u32, f32)|
+	{
+		Window::getAudio().modify(x.0, |v|
+		{
+			v.position = None;
+			v.pan = x.1.clamp(-1.0, 1.0);
+		});
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setRadius",
+	script.create_function(|_, x: (u32, f32)|
+	{
+		Window::getAudio().modify(x.0, |v| v.radius = x.1.max(0.0));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setDefaultRadius",
+	script.create_function(|_, x: f32|
+	{
+		Window::getAudio().setRadius(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("playMusic",
+	script.create_function(|_, x: (String, Option<f32>)|
+	{
+		Window::getAudio().playMusic(x.0, x.1);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("stopMusic",
+	script.create_function(|_, fade: Option<f32>|
+	{
+		Window::getAudio().stopMusic(fade);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getMusic",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getAudio().getMusic())
+	}).unwrap());
+
+	let _ = t.raw_set("setMusicFade",
+	script.create_function(|_, x: f32|
+	{
+		Window::getAudio().setMusicFade(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setMusicVolume",
+	script.create_function(|_, x: f32|
+	{
+		Window::getInstance().prog.insert(
+			String::from("musicVolume"),
+			Variable::num(x.max(0.0))
+		);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getMusicVolume",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getInstance().prog.get("musicVolume")
+			.map(|x| x.num).unwrap_or(1.0))
+	}).unwrap());
+
+	let _ = t.raw_set("setMasterVolume",
+	script.create_function(|_, x: f32|
+	{
+		Window::getAudio().setVolume(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getMasterVolume",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getAudio().getVolume())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("audio", t);
+}
+
+pub fn input(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("action",
+	script.create_function(|_, name: String|
+	{
+		Ok(Window::getInput().isPressed(&name))
+	}).unwrap());
+
+	let _ = t.raw_set("actionJustPressed",
+	script.create_function(|_, name: String|
+	{
+		Ok(Window::getInput().isJustPressed(&name))
+	}).unwrap());
+
+	let _ = t.raw_set("actionJustReleased",
+	script.create_function(|_, name: String|
+	{
+		Ok(Window::getInput().isJustReleased(&name))
+	}).unwrap());
+
+	let _ = t.raw_set("value",
+	script.create_function(|_, name: String|
+	{
+		Ok(Window::getInput().value(&name))
+	}).unwrap());
+
+	let _ = t.raw_set("axis",
+	script.create_function(|_, name: String|
+	{
+		Ok(Window::getInput().axis(&name))
+	}).unwrap());
+
+	let _ = t.raw_set("getBindings",
+	script.create_function(|_, name: String|
+	{
+		Ok(Window::getInput().getBindings(&name))
+	}).unwrap());
+
+	let _ = t.raw_set("setBindings",
+	script.create_function(|_, x: (String, Vec<String>)|
+	{
+		Window::getInput().setBindings(x.0, x.1);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("lastPressed",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Input::lastPressed())
+	}).unwrap());
+
+	let _ = t.raw_set("save",
+	script.create_function(|_, _: ()|
+	{
+		Window::getInput().save();
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("reset",
+	script.create_function(|_, _: ()|
+	{
+		Window::getInput().reset();
+		Ok(())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("input", t);
+}
+
+pub fn gamepad(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("isConnected",
+	script.create_function(|_, id: u8|
+	{
+		Ok(Window::getGamepad().isConnected(id))
+	}).unwrap());
+
+	let _ = t.raw_set("name",
+	script.create_function(|_, id: u8|
+	{
+		Ok(Window::getGamepad().getName(id))
+	}).unwrap());
+
+	let _ = t.raw_set("pressed",
+	script.create_function(|_, x: (u8, String)|
+	{
+		Ok(Window::getGamepad().isPressed(x.0, &x.1))
+	}).unwrap());
+
+	let _ = t.raw_set("justPressed",
+	script.create_function(|_, x: (u8, String)|
+	{
+		Ok(Window::getGamepad().isJustPressed(x.0, &x.1))
+	}).unwrap());
+
+	let _ = t.raw_set("justReleased",
+	script.create_function(|_, x: (u8, String)|
+	{
+		Ok(Window::getGamepad().isJustReleased(x.0, &x.1))
+	}).unwrap());
+
+	let _ = t.raw_set("axis",
+	script.create_function(|_, x: (u8, String)|
+	{
+		Ok(Window::getGamepad().getAxis(x.0, &x.1))
+	}).unwrap());
+
+	let _ = t.raw_set("setDeadZone",
+	script.create_function(|_, x: f32|
+	{
+		Window::getGamepad().setDeadZone(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getDeadZone",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getGamepad().getDeadZone())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("gamepad", t);
+}
+
+pub fn textField(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("draw",
+	script.create_function(|s, _: ()|
+	{
+		Window::getCamera().draw(getTextField(s)?);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getString",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getTextField(s)?.getString())
+	}).unwrap());
+
+	let _ = t.raw_set("setString",
+	script.create_function(|s, x: String|
+	{
+		getTextField(s)?.setString(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setPlaceholder",
+	script.create_function(|s, x: String|
+	{
+		getTextField(s)?.setPlaceholder(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setPosition",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getTextField(s)?.getText().getTransformable().setPosition(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getPosition",
+	script.create_function(|s, _: ()|
+	{
+		let p = getTextField(s)?.getText().getTransformable().getPosition();
+		Ok((p.x, p.y))
+	}).unwrap());
+
+	let _ = t.raw_set("setScale",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getTextField(s)?.getText().getTransformable().setScale(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setWidth",
+	script.create_function(|s, x: f32|
+	{
+		getTextField(s)?.setWidth(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("bounds",
+	script.create_function(|s, _: ()|
+	{
+		let b = getTextField(s)?.getBounds();
+		Ok((b.x, b.y, b.z, b.w))
+	}).unwrap());
+
+	let _ = t.raw_set("focus",
+	script.create_function(|s, _: ()|
+	{
+		getTextField(s)?.setFocus(true);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("blur",
+	script.create_function(|s, _: ()|
+	{
+		getTextField(s)?.setFocus(false);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isFocused",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getTextField(s)?.isFocused())
+	}).unwrap());
+
+	let _ = t.raw_set("setMaxLength",
+	script.create_function(|s, x: usize|
+	{
+		getTextField(s)?.setMaxLength(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setMask",
+	script.create_function(|s, x: Option<String>|
+	{
+		getTextField(s)?.setMask(x.and_then(|m| m.chars().next()));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setColor",
+	script.create_function(|s, x: (u8, u8, u8, u8)|
+	{
+		getTextField(s)?.setColor(glam::vec4(
+			x.0 as f32 / 255.0,
+			x.1 as f32 / 255.0,
+			x.2 as f32 / 255.0,
+			x.3 as f32 / 255.0
+		));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setSelectionColor",
+	script.create_function(|s, x: (u8, u8, u8, u8)|
+	{
+		getTextField(s)?.setSelectionColor(glam::vec4(
+			x.0 as f32 / 255.0,
+			x.1 as f32 / 255.0,
+			x.2 as f32 / 255.0,
+			x.3 as f32 / 255.0
+		));
+		Ok(())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("textField", t);
+}
+
+pub fn loc(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("get",
+	script.create_function(|_, x: (String, mlua::Variadic<mlua::Value>)|
+	{
+		let args: Vec<String> = x.1.iter()
+			.map(|v| v.to_string().unwrap_or_default())
+			.collect();
+		Ok(Window::getLocale().get(&x.0, &args))
+	}).unwrap());
+
+	let _ = t.raw_set("has",
+	script.create_function(|_, key: String|
+	{
+		Ok(Window::getLocale().has(&key))
+	}).unwrap());
+
+	let _ = t.raw_set("setLanguage",
+	script.create_function(|_, code: String|
+	{
+		Ok(Window::setLanguage(code))
+	}).unwrap());
+
+	let _ = t.raw_set("getLanguage",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getLocale().getLanguage())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("loc", t);
+}
+
+pub fn layout(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("rect",
+	script.create_function(|s, _: ()|
+	{
+		let r = getLayout(s)?.rect;
+		Ok((r.x, r.y, r.z, r.w))
+	}).unwrap());
+
+	let _ = t.raw_set("setSize",
+	script.create_function(|s, x: (mlua::Value, mlua::Value)|
+	{
+		getLayout(s)?.size = (lengthFromLua(x.0), lengthFromLua(x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setPosition",
+	script.create_function(|s, x: (mlua::Value, mlua::Value)|
+	{
+		getLayout(s)?.offset = (lengthFromLua(x.0), lengthFromLua(x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setAnchor",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getLayout(s)?.anchor = glam::vec2(x.0, x.1);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setPivot",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getLayout(s)?.pivot = glam::vec2(x.0, x.1);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("scale",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getUI().getScale())
+	}).unwrap());
+
+	let _ = t.raw_set("refresh",
+	script.create_function(|_, _: ()|
+	{
+		Window::getUI().relayout();
+		Ok(())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("layout", t);
+}
+
+pub fn node(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("path",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getNode(s)?.getPath())
+	}).unwrap());
+
+	let _ = t.raw_set("parent",
+	script.create_function(|s, _: ()|
+	{
+		Ok(Window::getUI().getParent(&getNode(s)?.getPath()))
+	}).unwrap());
+
+	let _ = t.raw_set("children",
+	script.create_function(|s, _: ()|
+	{
+		Ok(Window::getUI().getChildren(&getNode(s)?.getPath()))
+	}).unwrap());
+
+	let _ = t.raw_set("setPosition",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getNode(s)?.getTransformable().setPosition(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("translate",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getNode(s)?.getTransformable().translate(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getPosition",
+	script.create_function(|s, _: ()|
+	{
+		let p = getNode(s)?.getTransformable().getPosition();
+		Ok((p.x, p.y))
+	}).unwrap());
+
+	let _ = t.raw_set("setRotation",
+	script.create_function(|s, x: f32|
+	{
+		getNode(s)?.getTransformable().setRotation(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getRotation",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getNode(s)?.getTransformable().getRotation())
+	}).unwrap());
+
+	let _ = t.raw_set("setScale",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getNode(s)?.getTransformable().setScale(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getScale",
+	script.create_function(|s, _: ()|
+	{
+		let x = getNode(s)?.getTransformable().getScale();
+		Ok((x.x, x.y))
+	}).unwrap());
+
+	let _ = t.raw_set("setOrigin",
+	script.create_function(|s, x: (f32, f32)|
+	{
+		getNode(s)?.getTransformable().setOrigin(glam::vec2(x.0, x.1));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setVisible",
+	script.create_function(|s, x: bool|
+	{
+		getNode(s)?.setVisible(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isVisible",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getNode(s)?.isVisible())
+	}).unwrap());
+
+	let _ = t.raw_set("setEnabled",
+	script.create_function(|s, x: bool|
+	{
+		getNode(s)?.setEnabled(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isEnabled",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getNode(s)?.isEnabled())
+	}).unwrap());
+
+	let _ = t.raw_set("setOpacity",
+	script.create_function(|s, x: f32|
+	{
+		getNode(s)?.setOpacity(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getOpacity",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getNode(s)?.getOpacity())
+	}).unwrap());
+
+	let _ = t.raw_set("focus",
+	script.create_function(|s, _: ()|
+	{
+		Window::getUI().focus(&getNode(s)?.getPath());
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("blur",
+	script.create_function(|s, _: ()|
+	{
+		let ui = Window::getUI();
+		if ui.getFocused() == Some(getNode(s)?.getPath()) { ui.blur(); }
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isFocused",
+	script.create_function(|s, _: ()|
+	{
+		Ok(Window::getUI().getFocused() == Some(getNode(s)?.getPath()))
+	}).unwrap());
+
+	let _ = t.raw_set("isHovered",
+	script.create_function(|s, _: ()|
+	{
+		Ok(Window::getUI().getHovered() == Some(getNode(s)?.getPath()))
+	}).unwrap());
+
+	let _ = script.globals().raw_set("node", t);
+}
+
+pub fn widget(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("setText",
+	script.create_function(|s, x: String|
+	{
+		getWidget(s)?.setText(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getText",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getWidget(s)?.getText())
+	}).unwrap());
+
+	let _ = t.raw_set("setChecked",
+	script.create_function(|s, x: bool|
+	{
+		getWidget(s)?.setChecked(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isChecked",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getWidget(s)?.isChecked())
+	}).unwrap());
+
+	let _ = t.raw_set("setValue",
+	script.create_function(|s, x: f32|
+	{
+		getWidget(s)?.setValue(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getValue",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getWidget(s)?.getValue())
+	}).unwrap());
+
+	let _ = t.raw_set("setRange",
+	script.create_function(|s, x: (f32, f32, Option<f32>)|
+	{
+		getWidget(s)?.setRange(x.0, x.1, x.2.unwrap_or(0.0));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("setItems",
+	script.create_function(|s, x: Vec<String>|
+	{
+		getWidget(s)?.setItems(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getItems",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getWidget(s)?.getItems())
+	}).unwrap());
+
+	// Indices start from 1 like in Lua tables, nil means nothing is selected
+	let _ = t.raw_set("setSelected",
+	script.create_function(|s, x: Option<usize>|
+	{
+		getWidget(s)?.setSelected(x.and_then(|x| x.checked_sub(1)));
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("getSelected",
+	script.create_function(|s, _: ()|
+	{
+		Ok(getWidget(s)?.getSelected().map(|x| x + 1))
+	}).unwrap());
+
+	let _ = t.raw_set("setSkin",
+	script.create_function(|s, x: (String, String, Option<f32>, Option<f32>, Option<f32>, Option<f32>)|
+	{
+		let node = match x.2
+		{
+			Some(l) => json::object!{
+				image: x.1,
+				insets: [l, x.3.unwrap_or(l), x.4.unwrap_or(l), x.5.unwrap_or(l)]
+			},
+			None => json::JsonValue::from(x.1)
+		};
+		if let Some(skin) = Skin::parse(&node) { getWidget(s)?.setSkin(x.0, skin); }
+		Ok(())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("widget", t);
+}
+
+pub fn tween(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("to",
+	script.create_function(|s, x: (String, Table, f32, Option<String>, Option<mlua::Function>)|
+	{
+		let step = tweenStep(s, x.0, x.1, x.2, x.3)?;
+		Ok(Window::getUI().getTweens().add(getNode(s)?.getPath(), vec![step], x.4))
+	}).unwrap());
+
+	// Every step is { target, props, duration, easing } or { wait = seconds }
+	let _ = t.raw_set("sequence",
+	script.create_function(|s, x: (Vec<Table>, Option<mlua::Function>)|
+	{
+		let mut steps = vec![];
+		for step in x.0
+		{
+			if let Some(w) = step.get::<Option<f32>>("wait")?
+			{
+				steps.push(Step::wait(w));
+				continue;
+			}
+			steps.push(tweenStep(s, step.get(1)?, step.get(2)?, step.get(3)?, step.get(4)?)?);
+		}
+		Ok(Window::getUI().getTweens().add(getNode(s)?.getPath(), steps, x.1))
+	}).unwrap());
+
+	let _ = t.raw_set("delay",
+	script.create_function(|s, x: (f32, mlua::Function)|
+	{
+		Ok(Window::getUI().getTweens().add(getNode(s)?.getPath(), vec![Step::wait(x.0)], Some(x.1)))
+	}).unwrap());
+
+	let _ = t.raw_set("cancel",
+	script.create_function(|_, x: u32|
+	{
+		Window::getUI().getTweens().cancel(x);
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("cancelAll",
+	script.create_function(|s, x: Option<String>|
+	{
+		let owner = getNode(s)?.getPath();
+		match x
+		{
+			Some(target) =>
+			{
+				let (path, part) = tweenTarget(s, &target);
+				Window::getUI().getTweens().cancelAll(&owner, Some((&path, part)));
+			}
+			None => Window::getUI().getTweens().cancelAll(&owner, None)
+		}
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isActive",
+	script.create_function(|_, x: u32|
+	{
+		Ok(Window::getUI().getTweens().isActive(x))
+	}).unwrap());
+
+	let _ = script.globals().raw_set("tween", t);
+}
+
+pub fn resources(script: &Lua)
+{
+	let t = script.create_table().unwrap();
+
+	let _ = t.raw_set("preload",
+	script.create_function(|_, x: mlua::Value|
+	{
+		for path in pathsFromLua(x) { Window::getResources().preload(path); }
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("release",
+	script.create_function(|_, x: mlua::Value|
+	{
+		for path in pathsFromLua(x) { Window::getResources().release(&path); }
+		Ok(())
+	}).unwrap());
+
+	let _ = t.raw_set("isLoaded",
+	script.create_function(|_, path: String|
+	{
+		Ok(Window::getResources().isLoaded(&path))
+	}).unwrap());
+
+	let _ = t.raw_set("isLoading",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getResources().isLoading())
+	}).unwrap());
+
+	let _ = t.raw_set("getProgress",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getResources().getProgress())
+	}).unwrap());
+
+	let _ = t.raw_set("getTextureCount",
+	script.create_function(|_, _: ()|
+	{
+		Ok(Window::getResources().getTextureCount())
+	}).unwrap());
+
+	let _ = script.globals().raw_set("resources", t);
+}
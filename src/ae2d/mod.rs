@@ -13,3 +13,13 @@ pub mod Entity;
 pub mod Skeleton;
 pub mod Shapes;
 pub mod Profiler;
+pub mod Audio;
+pub mod Input;
+pub mod Gamepad;
+pub mod TextField;
+pub mod Locale;
+pub mod Layout;
+pub mod Widget;
+pub mod Tween;
+pub mod Watcher;
+pub mod Resources;
\ No newline at end of file
@@ -0,0 +1,157 @@
+#![allow(non_snake_case)]
+
+// Bakes a TTF/OTF font into the JSON + PNG format read by Font::load.
+// Usage: fontbake <font.ttf> <size> <charset> <output>
+// <charset> is either a text file with the characters or the characters themselves,
+// "default" bakes printable ASCII and cyrillic.
+// <output> is the path without extension, <output>.png and <output>.json are written.
+
+fn usage()
+{
+	println!("Usage: fontbake <font.ttf> <size> <charset|default|file.txt> <output>");
+}
+
+fn defaultCharset() -> String
+{
+	(' '..='~').chain('А'..='я').chain(['Ё', 'ё']).collect()
+}
+
+fn main()
+{
+	let args: Vec<String> = std::env::args().collect();
+	if args.len() < 5 { usage(); return; }
+
+	let size: f32 = match args[2].parse()
+	{
+		Ok(x) => x,
+		Err(_) => { println!("Wrong font size: {}", args[2]); return; }
+	};
+
+	let mut charset = match args[3].as_str()
+	{
+		"default" => defaultCharset(),
+		x => std::fs::read_to_string(x).unwrap_or(x.to_string())
+	}.chars().filter(|c| !c.is_control()).collect::<Vec<char>>();
+	charset.sort();
+	charset.dedup();
+
+	// Glyph ids are 16-bit in the engine
+	let wide: String = charset.iter().filter(|c| **c as u32 > 0xFFFF).collect();
+	if !wide.is_empty()
+	{
+		println!("Skipping characters above U+FFFF: {wide}");
+		charset.retain(|c| *c as u32 <= 0xFFFF);
+	}
+
+	let data = match std::fs::read(&args[1])
+	{
+		Ok(x) => x,
+		Err(x) => { println!("Failed to read font {}: {x}", args[1]); return; }
+	};
+	let font = match fontdue::Font::from_bytes(data, fontdue::FontSettings
+	{
+		scale: size,
+		..Default::default()
+	})
+	{
+		Ok(x) => x,
+		Err(x) => { println!("Failed to parse font {}: {x}", args[1]); return; }
+	};
+
+	let (ascent, descent) = match font.horizontal_line_metrics(size)
+	{
+		Some(lm) => (lm.ascent, lm.descent),
+		None =>
+		{
+			println!("Font {} has no horizontal metrics, the line height is guessed", args[1]);
+			(size * 0.8, -size * 0.2)
+		}
+	};
+
+	let glyphs: Vec<(char, fontdue::Metrics, Vec<u8>)> = charset.iter()
+		.filter(|c| font.lookup_glyph_index(**c) != 0 || c.is_whitespace())
+		.map(|c| { let (m, b) = font.rasterize(*c, size); (*c, m, b) })
+		.collect();
+
+	let area: usize = glyphs.iter().map(|g| (g.1.width + 1) * (g.1.height + 1)).sum();
+	let width = ((area as f32).sqrt().ceil() as usize).next_power_of_two().max(64);
+
+	// Shelf packing, same as the runtime atlas
+	let mut rects = vec![];
+	let (mut x, mut y, mut row) = (0, 0, 0);
+	for (_, m, _) in &glyphs
+	{
+		if x + m.width + 1 > width { x = 0; y += row + 1; row = 0; }
+		rects.push((x, y));
+		x += m.width + 1;
+		row = row.max(m.height);
+	}
+	let height = (y + row + 1).next_power_of_two();
+
+	let mut pixels = vec![0u8; width * height * 4];
+	let mut list = json::JsonValue::new_array();
+	for ((c, m, bitmap), (px, py)) in glyphs.iter().zip(&rects)
+	{
+		for gy in 0..m.height
+		{
+			for gx in 0..m.width
+			{
+				let i = ((py + gy) * width + px + gx) * 4;
+				pixels[i..i + 4].copy_from_slice(&[255, 255, 255, bitmap[gy * m.width + gx]]);
+			}
+		}
+		let _ = list.push(json::object!{
+			id: *c as u32,
+			x: *px,
+			y: *py,
+			w: m.width,
+			h: m.height,
+			ox: m.xmin,
+			oy: ascent - m.ymin as f32 - m.height as f32,
+			advance: m.advance_width
+		});
+	}
+
+	let out = &args[4];
+	let png = String::from(out) + ".png";
+	let file = match std::fs::File::create(&png)
+	{
+		Ok(x) => x,
+		Err(x) => { println!("Failed to create {png}: {x}"); return; }
+	};
+	let mut enc = png::Encoder::new(std::io::BufWriter::new(file), width as u32, height as u32);
+	enc.set_color(png::ColorType::Rgba);
+	enc.set_depth(png::BitDepth::Eight);
+	if let Err(x) = enc.write_header().and_then(|mut w| w.write_image_data(&pixels))
+	{
+		println!("Failed to write {png}: {x}");
+		return;
+	}
+
+	let mut kerning = json::JsonValue::new_array();
+	for (a, _, _) in &glyphs
+	{
+		for (b, _, _) in &glyphs
+		{
+			if let Some(k) = font.horizontal_kern(*a, *b, size).filter(|k| *k != 0.0)
+			{
+				let _ = kerning.push(json::object!{ first: *a as u32, second: *b as u32, amount: k });
+			}
+		}
+	}
+
+	let desc = json::object!{
+		lineHeight: (ascent - descent).ceil(),
+		texture: png.clone(),
+		glyphs: list,
+		kerning: kerning
+	};
+	let path = String::from(out) + ".json";
+	if let Err(x) = std::fs::write(&path, desc.pretty(4))
+	{
+		println!("Failed to write {path}: {x}");
+		return;
+	}
+
+	println!("Baked {} glyphs into {width}x{height} atlas: {path}", glyphs.len());
+}
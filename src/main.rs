@@ -9,9 +9,20 @@ fn main()
 {
 	Window::init("res/global/config.json");
 	Window::resetDT();
+	let mut accumulator = 0.0;
 	while Window::isOpen()
 	{
 		Window::update();
+		if let Some(step) = Window::getTickStep()
+		{
+			accumulator += Window::getFrameTime();
+			while accumulator >= step
+			{
+				Window::tick();
+				accumulator -= step;
+			}
+			Window::setAlpha(accumulator / step);
+		}
 		Window::render();
 	}
 }